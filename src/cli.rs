@@ -1,16 +1,64 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use solana_sdk::pubkey::Pubkey;
 use std::path::PathBuf;
+use std::str::FromStr;
 
 #[derive(Parser)]
 #[command(name = "stealth-launch")]
 #[command(about = "Private token creation on Solana using Token2022 confidential extensions")]
 #[command(version)]
 pub struct Cli {
+    /// Output format; `json` prints one result document on stdout and progress on stderr
+    /// (create, configure, distribute, estimate and balance)
+    #[arg(long, global = true, value_enum, default_value = "text")]
+    pub output: OutputFormat,
+
+    /// Build and simulate every transaction without sending anything (create, configure
+    /// and distribute)
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Priority fee in micro-lamports per compute unit, or `auto` to pay what recent
+    /// transactions on the same accounts paid
+    #[arg(long, global = true)]
+    pub priority_fee: Option<PriorityFee>,
+
+    /// Compute unit limit requested by every transaction
+    #[arg(long, global = true)]
+    pub compute_unit_limit: Option<u32>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable progress and results
+    Text,
+    /// A single JSON document (create, configure, distribute, estimate, balance)
+    Json,
+}
+
+#[derive(Clone, Copy)]
+pub enum PriorityFee {
+    MicroLamports(u64),
+    /// Sample `getRecentPrioritizationFees` for the accounts each transaction locks
+    Auto,
+}
+
+impl FromStr for PriorityFee {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "auto" {
+            return Ok(PriorityFee::Auto);
+        }
+        s.parse()
+            .map(PriorityFee::MicroLamports)
+            .map_err(|_| format!("expected micro-lamports per compute unit or `auto`, got {}", s))
+    }
+}
+
 #[derive(Subcommand)]
 pub enum Commands {
     /// Create a new token with confidential transfer extensions
@@ -19,8 +67,42 @@ pub enum Commands {
     Configure(ConfigureArgs),
     /// Distribute tokens via confidential transfers
     Distribute(DistributeArgs),
+    /// Estimate the SOL a launch or distribution needs and compare it to the payer's balance
+    Estimate(EstimateArgs),
     /// Check confidential balance for a wallet
     Balance(BalanceArgs),
+    /// Move pending confidential balance into the available balance
+    ApplyPending(ApplyPendingArgs),
+    /// Allow confidential transfers into your token account again
+    EnableCredits(EnableCreditsArgs),
+    /// Withdraw confidential balance back to the public balance
+    Withdraw(WithdrawArgs),
+    /// Deposit public token balance into the confidential balance
+    Deposit(DepositArgs),
+    /// Send a single confidential transfer
+    Transfer(TransferArgs),
+    /// Close orphaned proof context accounts and recover their rent
+    Reclaim(ReclaimArgs),
+    /// Rewrite the decryptable balance from the encrypted available balance
+    RepairBalance(RepairBalanceArgs),
+    /// Burn tokens from the confidential balance (confidential supply mints only)
+    Burn(BurnArgs),
+    /// Harvest confidential withheld transfer fees and withdraw them to the authority
+    HarvestFees(HarvestFeesArgs),
+    /// Show your confidential transaction history with decrypted amounts
+    History(HistoryArgs),
+    /// Approve token accounts on a mint that requires manual approval
+    ApproveAccount(ApproveAccountArgs),
+    /// List token accounts waiting for confidential transfer approval
+    PendingApprovals(PendingApprovalsArgs),
+    /// Show or update the mint's confidential transfer configuration
+    MintConfig(MintConfigArgs),
+    /// Decrypt auditor ciphertexts from the mint's transfer history into a ledger
+    Audit(AuditArgs),
+    /// Generate an auditor ElGamal keypair
+    AuditorKeygen(AuditorKeygenArgs),
+    /// Inspect auditor keys
+    Auditor(AuditorArgs),
 }
 
 #[derive(Parser)]
@@ -33,10 +115,18 @@ pub struct CreateArgs {
     #[arg(long)]
     pub symbol: String,
 
-    /// Initial supply (hidden from public)
+    /// Token metadata URI (JSON with image, description, etc.)
+    #[arg(long, default_value = "")]
+    pub uri: String,
+
+    /// Initial supply (public unless --confidential-supply is passed)
     #[arg(long)]
     pub supply: u64,
 
+    /// Keep the supply confidential with the ConfidentialMintBurn extension (disables deposit/withdraw)
+    #[arg(long)]
+    pub confidential_supply: bool,
+
     /// Token decimals
     #[arg(long, default_value = "9")]
     pub decimals: u8,
@@ -45,6 +135,42 @@ pub struct CreateArgs {
     #[arg(long)]
     pub auditor: Option<String>,
 
+    /// Require the confidential transfer authority to approve each new token account
+    #[arg(long)]
+    pub require_approval: bool,
+
+    /// Transfer fee in basis points (adds TransferFeeConfig and ConfidentialTransferFeeConfig)
+    #[arg(long, requires = "max_fee")]
+    pub fee_basis_points: Option<u16>,
+
+    /// Maximum transfer fee per transfer (in base units)
+    #[arg(long, requires = "fee_basis_points")]
+    pub max_fee: Option<u64>,
+
+    /// Directory to write the launch receipt (and mint keypair, if requested) to
+    #[arg(long)]
+    pub out_dir: Option<PathBuf>,
+
+    /// Also save the generated mint keypair to --out-dir
+    #[arg(long, requires = "out_dir")]
+    pub save_mint_keypair: bool,
+
+    /// Use an existing keypair for the mint instead of generating one
+    #[arg(long, conflicts_with_all = ["grind_prefix", "grind_suffix"])]
+    pub mint_keypair: Option<PathBuf>,
+
+    /// Grind for a mint address starting with this base58 prefix
+    #[arg(long)]
+    pub grind_prefix: Option<String>,
+
+    /// Grind for a mint address ending with this base58 suffix
+    #[arg(long)]
+    pub grind_suffix: Option<String>,
+
+    /// Resume a partially completed launch for this mint, running only the missing steps
+    #[arg(long, conflicts_with_all = ["grind_prefix", "grind_suffix"])]
+    pub resume: Option<Pubkey>,
+
     /// Path to payer keypair
     #[arg(long, default_value = "~/.config/solana/id.json")]
     pub keypair: PathBuf,
@@ -79,6 +205,76 @@ pub struct DistributeArgs {
     #[arg(long)]
     pub mint: Pubkey,
 
+    /// CSV file with wallet,amount rows
+    #[arg(long, required_unless_present_any = ["resume", "retry_pending"])]
+    pub recipients: Option<PathBuf>,
+
+    /// Where to write the run journal (defaults to distribute-<mint>-<timestamp>.json)
+    #[arg(long)]
+    pub journal: Option<PathBuf>,
+
+    /// Resume an interrupted distribution from its journal, skipping completed transfers
+    #[arg(long, conflicts_with_all = ["recipients", "journal"])]
+    pub resume: Option<PathBuf>,
+
+    /// Only check recipient readiness and print a report; send nothing
+    #[arg(long)]
+    pub check: bool,
+
+    /// Write the readiness report as CSV (wallet,amount,status)
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+
+    /// What to do with recipients that are not ready for confidential transfers
+    #[arg(long, value_enum, default_value = "abort")]
+    pub unready: UnreadyPolicy,
+
+    /// Queue file for parked recipients; parked rows are added to it if it exists
+    /// (defaults to pending-<mint>-<timestamp>.csv)
+    #[arg(long)]
+    pub pending_queue: Option<PathBuf>,
+
+    /// Pay recipients from a pending queue who have since configured their accounts
+    #[arg(long, conflicts_with_all = ["recipients", "resume", "pending_queue"])]
+    pub retry_pending: Option<PathBuf>,
+
+    /// Payer/authority keypair
+    #[arg(long, default_value = "~/.config/solana/id.json")]
+    pub keypair: PathBuf,
+
+    /// RPC endpoint
+    #[arg(long, default_value = "https://zk-edge.surfnet.dev:8899")]
+    pub rpc: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum UnreadyPolicy {
+    /// Refuse to start if any recipient is not ready
+    Abort,
+    /// Park unready recipients in a pending queue and pay the rest
+    Park,
+}
+
+#[derive(Parser)]
+pub struct EstimateArgs {
+    #[command(subcommand)]
+    pub command: EstimateCommands,
+}
+
+#[derive(Subcommand)]
+pub enum EstimateCommands {
+    /// Estimate a `create` launch with the same arguments
+    Create(CreateArgs),
+    /// Estimate a `distribute` run over a recipients CSV
+    Distribute(EstimateDistributeArgs),
+}
+
+#[derive(Parser)]
+pub struct EstimateDistributeArgs {
+    /// Mint address
+    #[arg(long)]
+    pub mint: Pubkey,
+
     /// CSV file with wallet,amount rows
     #[arg(long)]
     pub recipients: PathBuf,
@@ -110,3 +306,331 @@ pub struct BalanceArgs {
     #[arg(long, default_value = "https://zk-edge.surfnet.dev:8899")]
     pub rpc: String,
 }
+
+#[derive(Parser)]
+pub struct ApplyPendingArgs {
+    /// Mint address
+    #[arg(long)]
+    pub mint: Pubkey,
+
+    /// Owner keypair (needed to decrypt pending balance)
+    #[arg(long, default_value = "~/.config/solana/id.json")]
+    pub keypair: PathBuf,
+
+    /// RPC endpoint
+    #[arg(long, default_value = "https://zk-edge.surfnet.dev:8899")]
+    pub rpc: String,
+}
+
+#[derive(Parser)]
+pub struct EnableCreditsArgs {
+    /// Mint address
+    #[arg(long)]
+    pub mint: Pubkey,
+
+    /// Owner keypair
+    #[arg(long, default_value = "~/.config/solana/id.json")]
+    pub keypair: PathBuf,
+
+    /// RPC endpoint
+    #[arg(long, default_value = "https://zk-edge.surfnet.dev:8899")]
+    pub rpc: String,
+}
+
+#[derive(Parser)]
+pub struct WithdrawArgs {
+    /// Mint address
+    #[arg(long)]
+    pub mint: Pubkey,
+
+    /// Amount to withdraw (in base units)
+    #[arg(long)]
+    pub amount: u64,
+
+    /// Owner keypair
+    #[arg(long, default_value = "~/.config/solana/id.json")]
+    pub keypair: PathBuf,
+
+    /// RPC endpoint
+    #[arg(long, default_value = "https://zk-edge.surfnet.dev:8899")]
+    pub rpc: String,
+}
+
+#[derive(Parser)]
+pub struct DepositArgs {
+    /// Mint address
+    #[arg(long)]
+    pub mint: Pubkey,
+
+    /// Amount to deposit (in base units)
+    #[arg(long)]
+    pub amount: u64,
+
+    /// Also apply the pending balance after depositing
+    #[arg(long)]
+    pub apply: bool,
+
+    /// Owner keypair
+    #[arg(long, default_value = "~/.config/solana/id.json")]
+    pub keypair: PathBuf,
+
+    /// RPC endpoint
+    #[arg(long, default_value = "https://zk-edge.surfnet.dev:8899")]
+    pub rpc: String,
+}
+
+#[derive(Parser)]
+pub struct TransferArgs {
+    /// Mint address
+    #[arg(long)]
+    pub mint: Pubkey,
+
+    /// Recipient wallet
+    #[arg(long)]
+    pub to: Pubkey,
+
+    /// Amount to transfer (in base units)
+    #[arg(long)]
+    pub amount: u64,
+
+    /// Optional memo attached to the transfer transaction
+    #[arg(long)]
+    pub memo: Option<String>,
+
+    /// Payer/authority keypair
+    #[arg(long, default_value = "~/.config/solana/id.json")]
+    pub keypair: PathBuf,
+
+    /// RPC endpoint
+    #[arg(long, default_value = "https://zk-edge.surfnet.dev:8899")]
+    pub rpc: String,
+}
+
+#[derive(Parser)]
+pub struct ReclaimArgs {
+    /// Context state authority keypair (the payer used for distribute/transfer)
+    #[arg(long, default_value = "~/.config/solana/id.json")]
+    pub keypair: PathBuf,
+
+    /// Distribution journal whose in-flight proof accounts must be kept for `--resume`
+    /// (repeatable; journals in the working directory are always checked)
+    #[arg(long)]
+    pub journal: Vec<PathBuf>,
+
+    /// Close accounts without asking for confirmation
+    #[arg(long)]
+    pub yes: bool,
+
+    /// RPC endpoint
+    #[arg(long, default_value = "https://zk-edge.surfnet.dev:8899")]
+    pub rpc: String,
+}
+
+#[derive(Parser)]
+pub struct RepairBalanceArgs {
+    /// Mint address
+    #[arg(long)]
+    pub mint: Pubkey,
+
+    /// Owner keypair (needed to decrypt and rewrite the balance)
+    #[arg(long, default_value = "~/.config/solana/id.json")]
+    pub keypair: PathBuf,
+
+    /// Highest available balance to search for (defaults to the mint's supply; required
+    /// for --confidential-supply mints and supplies of 2^48 or more)
+    #[arg(long)]
+    pub max_amount: Option<u64>,
+
+    /// RPC endpoint
+    #[arg(long, default_value = "https://zk-edge.surfnet.dev:8899")]
+    pub rpc: String,
+}
+
+#[derive(Parser)]
+pub struct BurnArgs {
+    /// Mint address
+    #[arg(long)]
+    pub mint: Pubkey,
+
+    /// Amount to burn (in base units)
+    #[arg(long)]
+    pub amount: u64,
+
+    /// Owner keypair
+    #[arg(long, default_value = "~/.config/solana/id.json")]
+    pub keypair: PathBuf,
+
+    /// RPC endpoint
+    #[arg(long, default_value = "https://zk-edge.surfnet.dev:8899")]
+    pub rpc: String,
+}
+
+#[derive(Parser)]
+pub struct HarvestFeesArgs {
+    /// Mint address
+    #[arg(long)]
+    pub mint: Pubkey,
+
+    /// Withdraw withheld authority keypair (the mint authority used at create)
+    #[arg(long, default_value = "~/.config/solana/id.json")]
+    pub keypair: PathBuf,
+
+    /// RPC endpoint
+    #[arg(long, default_value = "https://zk-edge.surfnet.dev:8899")]
+    pub rpc: String,
+}
+
+#[derive(Parser)]
+pub struct HistoryArgs {
+    /// Mint address
+    #[arg(long)]
+    pub mint: Pubkey,
+
+    /// Owner keypair
+    #[arg(long, default_value = "~/.config/solana/id.json")]
+    pub keypair: PathBuf,
+
+    /// RPC endpoint
+    #[arg(long, default_value = "https://zk-edge.surfnet.dev:8899")]
+    pub rpc: String,
+}
+
+#[derive(Parser)]
+pub struct ApproveAccountArgs {
+    /// Mint address
+    #[arg(long)]
+    pub mint: Pubkey,
+
+    /// Wallet whose token account to approve (repeatable)
+    #[arg(long, required_unless_present = "all")]
+    pub wallet: Vec<Pubkey>,
+
+    /// Approve every token account currently pending approval
+    #[arg(long, conflicts_with = "wallet")]
+    pub all: bool,
+
+    /// Confidential transfer authority keypair
+    #[arg(long, default_value = "~/.config/solana/id.json")]
+    pub keypair: PathBuf,
+
+    /// RPC endpoint
+    #[arg(long, default_value = "https://zk-edge.surfnet.dev:8899")]
+    pub rpc: String,
+}
+
+#[derive(Parser)]
+pub struct PendingApprovalsArgs {
+    /// Mint address
+    #[arg(long)]
+    pub mint: Pubkey,
+
+    /// RPC endpoint
+    #[arg(long, default_value = "https://zk-edge.surfnet.dev:8899")]
+    pub rpc: String,
+}
+
+#[derive(Parser)]
+pub struct MintConfigArgs {
+    /// Mint address
+    #[arg(long)]
+    pub mint: Pubkey,
+
+    /// New auditor ElGamal pubkey (base58)
+    #[arg(long)]
+    pub auditor: Option<String>,
+
+    /// Remove the auditor
+    #[arg(long, conflicts_with = "auditor")]
+    pub remove_auditor: bool,
+
+    /// Whether newly configured token accounts are approved automatically
+    #[arg(long)]
+    pub auto_approve: Option<bool>,
+
+    /// Transfer the confidential transfer authority to this pubkey
+    #[arg(long)]
+    pub new_authority: Option<Pubkey>,
+
+    /// Apply changes without asking for confirmation
+    #[arg(long)]
+    pub yes: bool,
+
+    /// Confidential transfer authority keypair
+    #[arg(long, default_value = "~/.config/solana/id.json")]
+    pub keypair: PathBuf,
+
+    /// RPC endpoint
+    #[arg(long, default_value = "https://zk-edge.surfnet.dev:8899")]
+    pub rpc: String,
+}
+
+#[derive(Parser)]
+pub struct AuditArgs {
+    /// Mint address
+    #[arg(long)]
+    pub mint: Pubkey,
+
+    /// Auditor ElGamal keypair file (JSON)
+    #[arg(long)]
+    pub auditor_keypair: PathBuf,
+
+    /// Where to write the ledger
+    #[arg(long)]
+    pub out: PathBuf,
+
+    /// Ledger format
+    #[arg(long, value_enum, default_value = "csv")]
+    pub format: LedgerFormat,
+
+    /// RPC endpoint
+    #[arg(long, default_value = "https://zk-edge.surfnet.dev:8899")]
+    pub rpc: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LedgerFormat {
+    /// One row per transfer
+    Csv,
+    /// A JSON array of transfers
+    Json,
+}
+
+#[derive(Parser)]
+pub struct AuditorKeygenArgs {
+    /// Where to write the auditor ElGamal keypair
+    #[arg(long)]
+    pub outfile: PathBuf,
+
+    /// Derive the key deterministically from this signer keypair instead of generating it randomly
+    #[arg(long)]
+    pub from_keypair: Option<PathBuf>,
+
+    /// Seed message signed by --from-keypair to derive the key
+    #[arg(long, default_value = "auditor", requires = "from_keypair")]
+    pub seed: String,
+
+    /// Overwrite --outfile if it already exists
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Parser)]
+pub struct AuditorArgs {
+    #[command(subcommand)]
+    pub command: AuditorCommands,
+}
+
+#[derive(Subcommand)]
+pub enum AuditorCommands {
+    /// Generate an auditor ElGamal keypair (same as `auditor-keygen`)
+    Keygen(AuditorKeygenArgs),
+    /// Print the base58 pubkey of an auditor keypair file
+    Show(AuditorShowArgs),
+}
+
+#[derive(Parser)]
+pub struct AuditorShowArgs {
+    /// Auditor ElGamal keypair file
+    #[arg(long)]
+    pub keypair: PathBuf,
+}
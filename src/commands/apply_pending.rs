@@ -0,0 +1,97 @@
+use crate::cli::ApplyPendingArgs;
+use crate::commands::balance::{decode_balances, PendingBalance};
+use crate::compute_budget;
+use crate::config::{expand_path, load_keypair};
+use crate::crypto::ConfidentialKeys;
+use anyhow::{anyhow, Context, Result};
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::signature::Signer;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::extension::{
+    confidential_transfer::{instruction::apply_pending_balance, ConfidentialTransferAccount},
+    BaseStateWithExtensions, StateWithExtensions,
+};
+
+pub async fn execute(args: ApplyPendingArgs) -> Result<()> {
+    let keypair = load_keypair(&expand_path(&args.keypair))?;
+    let owner_keys = ConfidentialKeys::derive_from_keypair(&keypair)?;
+
+    let rpc_client = NonblockingRpcClient::new_with_commitment(
+        args.rpc.clone(),
+        CommitmentConfig::confirmed(),
+    );
+
+    let ata = get_associated_token_address_with_program_id(
+        &keypair.pubkey(),
+        &args.mint,
+        &spl_token_2022::id(),
+    );
+
+    status!("Applying pending confidential balance...");
+    status!("  Mint: {}", args.mint);
+    status!("  Token Account: {}", ata);
+
+    let account_data = rpc_client
+        .get_account(&ata)
+        .await
+        .with_context(|| format!("Token account not found for wallet {}", keypair.pubkey()))?;
+
+    let account_state =
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)
+            .context("Failed to unpack token account")?;
+
+    let ct_account = account_state
+        .get_extension::<ConfidentialTransferAccount>()
+        .context("Confidential transfer extension not found on account")?;
+
+    // The instruction must carry the credit counter we decrypted against, and the
+    // new decryptable balance must be current available + everything pending.
+    let expected_credit_counter = u64::from(ct_account.pending_balance_credit_counter);
+
+    if expected_credit_counter == 0 {
+        status!("\nNo pending balance to apply.");
+        return Ok(());
+    }
+
+    status!("  Pending credits: {}", expected_credit_counter);
+    status!("  Decrypting pending balance...");
+
+    let balances = decode_balances(&account_data.data, &owner_keys)?;
+    let PendingBalance::Decrypted(pending) = balances.pending else {
+        return Err(anyhow!("Failed to decrypt pending balance - you may not be the owner"));
+    };
+    let new_available_balance = balances
+        .available
+        .checked_add(pending)
+        .ok_or_else(|| anyhow!("Available balance overflow"))?;
+    let new_decryptable_balance = owner_keys.aes_key.encrypt(new_available_balance);
+
+    status!("  Pending balance: {}", pending);
+
+    let apply_ix = apply_pending_balance(
+        &spl_token_2022::id(),
+        &ata,
+        expected_credit_counter,
+        &new_decryptable_balance.into(),
+        &keypair.pubkey(),
+        &[],
+    )?;
+
+    let tx = compute_budget::signed_transaction_nonblocking(
+        &rpc_client,
+        &[apply_ix],
+        &keypair.pubkey(),
+        &[&keypair],
+    ).await?;
+
+    rpc_client
+        .send_and_confirm_transaction_with_spinner(&tx)
+        .await
+        .map_err(|e| anyhow!("Failed to apply pending balance: {}", e))?;
+
+    status!("\nPending balance applied.");
+    status!("Available balance: {}", new_available_balance);
+
+    Ok(())
+}
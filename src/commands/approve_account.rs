@@ -0,0 +1,107 @@
+use crate::cli::ApproveAccountArgs;
+use crate::commands::pending_approvals::fetch_pending_approvals;
+use crate::compute_budget;
+use crate::config::{create_rpc_client, expand_path, load_keypair};
+use anyhow::{anyhow, Context, Result};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::Signer,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::{
+            instruction::approve_account, ConfidentialTransferAccount, ConfidentialTransferMint,
+        },
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::{Account, Mint},
+};
+
+/// Approve instructions are tiny; this keeps each transaction well under the size limit.
+const APPROVALS_PER_TRANSACTION: usize = 10;
+
+pub async fn execute(args: ApproveAccountArgs) -> Result<()> {
+    let keypair = load_keypair(&expand_path(&args.keypair))?;
+    let rpc = create_rpc_client(&args.rpc);
+
+    status!("Approving token accounts for confidential transfers...");
+    status!("  Mint: {}", args.mint);
+
+    let mint_account_data = rpc.get_account(&args.mint)?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_account_data.data)?;
+    let ct_mint = mint_state.get_extension::<ConfidentialTransferMint>()?;
+    if Option::<Pubkey>::from(ct_mint.authority) != Some(keypair.pubkey()) {
+        return Err(anyhow!(
+            "{} is not the confidential transfer authority for mint {}",
+            keypair.pubkey(),
+            args.mint
+        ));
+    }
+
+    let accounts: Vec<Pubkey> = if args.all {
+        fetch_pending_approvals(&rpc, &args.mint)?
+            .into_iter()
+            .map(|p| p.account)
+            .collect()
+    } else {
+        let mut accounts = Vec::new();
+        for wallet in &args.wallet {
+            let ata = get_associated_token_address_with_program_id(
+                wallet,
+                &args.mint,
+                &spl_token_2022::id(),
+            );
+            let account_data = rpc
+                .get_account(&ata)
+                .with_context(|| format!("Token account not found for wallet {}", wallet))?;
+            let account_state = StateWithExtensions::<Account>::unpack(&account_data.data)
+                .context("Failed to unpack token account")?;
+            let ct = account_state
+                .get_extension::<ConfidentialTransferAccount>()
+                .map_err(|_| anyhow!("Wallet {} has not configured its token account yet", wallet))?;
+
+            if bool::from(ct.approved) {
+                status!("  {} already approved (skipping)", wallet);
+            } else {
+                accounts.push(ata);
+            }
+        }
+        accounts
+    };
+
+    if accounts.is_empty() {
+        status!("\nNothing to approve.");
+        return Ok(());
+    }
+
+    let authority = keypair.pubkey();
+    for batch in accounts.chunks(APPROVALS_PER_TRANSACTION) {
+        let instructions = batch
+            .iter()
+            .map(|account| {
+                approve_account(&spl_token_2022::id(), account, &args.mint, &authority, &[])
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let tx = compute_budget::signed_transaction(
+            &rpc,
+            &instructions,
+            &authority,
+            &[&keypair],
+        )?;
+
+        let signature = rpc
+            .send_and_confirm_transaction_with_spinner(&tx)
+            .context("Failed to approve token accounts")?;
+
+        for account in batch {
+            status!("  Approved {}", account);
+        }
+        status!("  Signature: {}", signature);
+    }
+
+    status!("\nApproved {} accounts", accounts.len());
+
+    Ok(())
+}
@@ -0,0 +1,315 @@
+use crate::cli::{AuditArgs, LedgerFormat};
+use crate::config::{create_rpc_client, expand_path, load_elgamal_keypair};
+use anyhow::{anyhow, Context, Result};
+use csv::Writer;
+use serde::Serialize;
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::{
+    message::compiled_instruction::CompiledInstruction,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::VersionedTransaction,
+};
+use solana_transaction_status_client_types::{UiLoadedAddresses, UiTransactionEncoding};
+use spl_token_2022::{
+    extension::confidential_transfer::instruction::{
+        ConfidentialTransferInstruction, TransferInstructionData, TransferWithFeeInstructionData,
+    },
+    instruction::{decode_instruction_data, decode_instruction_type, TokenInstruction},
+    solana_zk_sdk::encryption::{
+        elgamal::{ElGamalCiphertext, ElGamalSecretKey},
+        pod::elgamal::PodElGamalCiphertext,
+    },
+};
+use std::fs;
+use std::str::FromStr;
+
+/// getSignaturesForAddress returns at most this many signatures per call.
+const SIGNATURES_PER_PAGE: usize = 1000;
+
+/// A confidential transfer found in the mint's history. Token accounts are used for
+/// source and destination because that is what the instruction references.
+#[derive(Serialize)]
+pub struct LedgerEntry {
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub signature: String,
+    pub kind: &'static str,
+    pub source: String,
+    pub destination: String,
+    /// `None` when the ciphertexts were not encrypted to this auditor key
+    pub amount: Option<u64>,
+}
+
+/// Auditor ciphertexts of one confidential transfer instruction, still encrypted.
+pub struct ConfidentialTransferRecord {
+    pub kind: &'static str,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub auditor_ciphertext_lo: PodElGamalCiphertext,
+    pub auditor_ciphertext_hi: PodElGamalCiphertext,
+}
+
+pub async fn execute(args: AuditArgs) -> Result<()> {
+    let auditor = load_elgamal_keypair(&args.auditor_keypair)?;
+    let rpc = create_rpc_client(&args.rpc);
+
+    status!("Auditing confidential transfers...");
+    status!("  Mint: {}", args.mint);
+
+    let signatures = fetch_signatures(&rpc, &args.mint)?;
+    status!("  Transactions: {}", signatures.len());
+
+    let mut ledger = Vec::new();
+    let mut undecryptable = 0usize;
+
+    for signature in &signatures {
+        let (slot, block_time, records) = fetch_confidential_transfers(&rpc, signature)?;
+
+        for record in records {
+            let amount = decrypt_split_amount(
+                &record.auditor_ciphertext_lo,
+                &record.auditor_ciphertext_hi,
+                auditor.secret(),
+            );
+            if amount.is_none() {
+                undecryptable += 1;
+            }
+
+            ledger.push(LedgerEntry {
+                slot,
+                block_time,
+                signature: signature.to_string(),
+                kind: record.kind,
+                source: record.source.to_string(),
+                destination: record.destination.to_string(),
+                amount,
+            });
+        }
+    }
+
+    let out = expand_path(&args.out);
+    match args.format {
+        LedgerFormat::Csv => {
+            let mut writer = Writer::from_path(&out)
+                .with_context(|| format!("Failed to write ledger: {}", out.display()))?;
+            for entry in &ledger {
+                writer.serialize(entry)?;
+            }
+            writer.flush()?;
+        }
+        LedgerFormat::Json => {
+            fs::write(&out, serde_json::to_string_pretty(&ledger)?)
+                .with_context(|| format!("Failed to write ledger: {}", out.display()))?;
+        }
+    }
+
+    let total: u64 = ledger.iter().filter_map(|e| e.amount).sum();
+
+    status!("\nAudit complete.");
+    status!("Transfers: {}", ledger.len());
+    status!("Total decrypted volume: {}", total);
+    if undecryptable > 0 {
+        status!(
+            "Could not decrypt {} transfers (not encrypted to this auditor key)",
+            undecryptable
+        );
+    }
+    status!("Ledger: {}", out.display());
+
+    Ok(())
+}
+
+/// Pages through every successful transaction that touched `address`, oldest first.
+pub fn fetch_signatures(rpc: &RpcClient, address: &Pubkey) -> Result<Vec<Signature>> {
+    let mut signatures = Vec::new();
+    let mut before = None;
+
+    loop {
+        let page = rpc
+            .get_signatures_for_address_with_config(
+                address,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until: None,
+                    limit: Some(SIGNATURES_PER_PAGE),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )
+            .with_context(|| format!("Failed to fetch signatures for {}", address))?;
+
+        let page_len = page.len();
+        for status in page {
+            let signature = Signature::from_str(&status.signature)
+                .map_err(|_| anyhow!("Invalid signature from RPC: {}", status.signature))?;
+            before = Some(signature);
+            if status.err.is_none() {
+                signatures.push(signature);
+            }
+        }
+
+        if page_len < SIGNATURES_PER_PAGE {
+            break;
+        }
+    }
+
+    // RPC returns newest first
+    signatures.reverse();
+    Ok(signatures)
+}
+
+/// A confirmed transaction with the slot and time it landed.
+pub struct FetchedTransaction {
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub transaction: VersionedTransaction,
+    /// Static keys followed by addresses loaded from lookup tables (writable, then
+    /// readonly), which is the order instruction account indexes refer to
+    pub account_keys: Vec<Pubkey>,
+}
+
+impl FetchedTransaction {
+    /// The accounts `ix` references, or `None` if any index cannot be resolved.
+    pub fn instruction_accounts(&self, ix: &CompiledInstruction) -> Option<Vec<Pubkey>> {
+        ix.accounts
+            .iter()
+            .map(|i| self.account_keys.get(*i as usize).copied())
+            .collect()
+    }
+
+    /// The program `ix` invokes.
+    pub fn program_id(&self, ix: &CompiledInstruction) -> Option<&Pubkey> {
+        self.account_keys.get(ix.program_id_index as usize)
+    }
+}
+
+pub fn fetch_transaction(rpc: &RpcClient, signature: &Signature) -> Result<FetchedTransaction> {
+    let confirmed = rpc
+        .get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .with_context(|| format!("Failed to fetch transaction {}", signature))?;
+
+    let transaction = confirmed
+        .transaction
+        .transaction
+        .decode()
+        .ok_or_else(|| anyhow!("Failed to decode transaction {}", signature))?;
+
+    let mut account_keys = transaction.message.static_account_keys().to_vec();
+    let loaded = confirmed
+        .transaction
+        .meta
+        .and_then(|meta| Option::<UiLoadedAddresses>::from(meta.loaded_addresses));
+    if let Some(loaded) = loaded {
+        for address in loaded.writable.iter().chain(&loaded.readonly) {
+            let address = Pubkey::from_str(address)
+                .map_err(|_| anyhow!("Invalid loaded address from RPC: {}", address))?;
+            account_keys.push(address);
+        }
+    }
+
+    Ok(FetchedTransaction {
+        slot: confirmed.slot,
+        block_time: confirmed.block_time,
+        transaction,
+        account_keys,
+    })
+}
+
+/// Fetches a transaction and extracts its top-level confidential transfer instructions.
+pub fn fetch_confidential_transfers(
+    rpc: &RpcClient,
+    signature: &Signature,
+) -> Result<(u64, Option<i64>, Vec<ConfidentialTransferRecord>)> {
+    let fetched = fetch_transaction(rpc, signature)?;
+
+    let mut records = Vec::new();
+    for ix in fetched.transaction.message.instructions() {
+        if fetched.program_id(ix) != Some(&spl_token_2022::id()) {
+            continue;
+        }
+        // Source, mint and destination are always the first three accounts
+        let Some(accounts) = fetched.instruction_accounts(ix) else {
+            continue;
+        };
+        if accounts.len() < 3 {
+            continue;
+        }
+        if let Some((kind, lo, hi)) = decode_transfer_ciphertexts(&ix.data) {
+            records.push(ConfidentialTransferRecord {
+                kind,
+                source: accounts[0],
+                destination: accounts[2],
+                auditor_ciphertext_lo: lo,
+                auditor_ciphertext_hi: hi,
+            });
+        }
+    }
+
+    Ok((fetched.slot, fetched.block_time, records))
+}
+
+/// Splits token-2022 instruction data into its confidential transfer instruction type and
+/// the extension data (type byte included), or `None` for any other instruction.
+pub fn decode_confidential_transfer_instruction(
+    data: &[u8],
+) -> Option<(ConfidentialTransferInstruction, &[u8])> {
+    let (&tag, extension_data) = data.split_first()?;
+    if !matches!(
+        TokenInstruction::unpack(&[tag]),
+        Ok(TokenInstruction::ConfidentialTransferExtension)
+    ) {
+        return None;
+    }
+
+    let instruction_type = decode_instruction_type(extension_data).ok()?;
+    Some((instruction_type, extension_data))
+}
+
+/// Returns the auditor amount ciphertexts if `data` is a confidential `Transfer` or
+/// `TransferWithFee` instruction.
+fn decode_transfer_ciphertexts(
+    data: &[u8],
+) -> Option<(&'static str, PodElGamalCiphertext, PodElGamalCiphertext)> {
+    match decode_confidential_transfer_instruction(data)? {
+        (ConfidentialTransferInstruction::Transfer, extension_data) => {
+            let data = decode_instruction_data::<TransferInstructionData>(extension_data).ok()?;
+            Some((
+                "transfer",
+                data.transfer_amount_auditor_ciphertext_lo,
+                data.transfer_amount_auditor_ciphertext_hi,
+            ))
+        }
+        (ConfidentialTransferInstruction::TransferWithFee, extension_data) => {
+            let data =
+                decode_instruction_data::<TransferWithFeeInstructionData>(extension_data).ok()?;
+            Some((
+                "transfer_with_fee",
+                data.transfer_amount_auditor_ciphertext_lo,
+                data.transfer_amount_auditor_ciphertext_hi,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Decrypts an amount split into a 16-bit low and 32-bit high ciphertext.
+pub fn decrypt_split_amount(
+    lo: &PodElGamalCiphertext,
+    hi: &PodElGamalCiphertext,
+    secret: &ElGamalSecretKey,
+) -> Option<u64> {
+    let lo: ElGamalCiphertext = (*lo).try_into().ok()?;
+    let hi: ElGamalCiphertext = (*hi).try_into().ok()?;
+    let lo = lo.decrypt_u32(secret)?;
+    let hi = hi.decrypt_u32(secret)?;
+    Some(lo + (hi << 16))
+}
@@ -0,0 +1,49 @@
+use crate::cli::{AuditorArgs, AuditorCommands, AuditorKeygenArgs};
+use crate::config::{expand_path, load_elgamal_keypair, load_keypair, save_elgamal_keypair};
+use crate::crypto::format_elgamal_pubkey;
+use anyhow::{anyhow, Result};
+use spl_token_2022::solana_zk_sdk::encryption::elgamal::ElGamalKeypair;
+
+pub async fn execute(args: AuditorArgs) -> Result<()> {
+    match args.command {
+        AuditorCommands::Keygen(keygen_args) => keygen(keygen_args)?,
+        AuditorCommands::Show(show) => {
+            let auditor = load_elgamal_keypair(&show.keypair)?;
+            status!("{}", format_elgamal_pubkey(auditor.pubkey()));
+        }
+    }
+
+    Ok(())
+}
+
+/// `auditor-keygen`, also reachable as `auditor keygen`.
+pub fn keygen(args: AuditorKeygenArgs) -> Result<()> {
+    let outfile = expand_path(&args.outfile);
+    if outfile.exists() && !args.force {
+        return Err(anyhow!(
+            "{} already exists - pass --force to overwrite it",
+            outfile.display()
+        ));
+    }
+
+    let auditor = match &args.from_keypair {
+        Some(path) => {
+            let signer = load_keypair(&expand_path(path))?;
+            ElGamalKeypair::new_from_signer(&signer, args.seed.as_bytes())
+                .map_err(|e| anyhow!("Failed to derive ElGamal keypair: {}", e))?
+        }
+        None => ElGamalKeypair::new_rand(),
+    };
+
+    save_elgamal_keypair(&auditor, &outfile, args.force)?;
+
+    status!("Auditor keypair written to {}", outfile.display());
+    if args.from_keypair.is_some() {
+        status!("  (re-derivable from the signer with --seed \"{}\")", args.seed);
+    }
+    status!("\nAuditor ElGamal pubkey:");
+    status!("{}", format_elgamal_pubkey(auditor.pubkey()));
+    status!("\nPass it to `create --auditor <PUBKEY>` or `mint-config --auditor <PUBKEY>`.");
+
+    Ok(())
+}
@@ -1,12 +1,56 @@
 use crate::cli::BalanceArgs;
 use crate::config::{create_rpc_client, expand_path, load_keypair};
 use crate::crypto::ConfidentialKeys;
+use crate::output;
 use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
 use spl_associated_token_account::get_associated_token_address_with_program_id;
-use spl_token_2022::extension::{
-    confidential_transfer::ConfidentialTransferAccount,
-    BaseStateWithExtensions, StateWithExtensions,
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::ConfidentialTransferAccount, BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+    solana_zk_sdk::encryption::{
+        elgamal::{ElGamalCiphertext, ElGamalKeypair},
+        pedersen::PedersenOpening,
+    },
 };
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+/// Decrypted view of a confidential token account.
+pub struct DecodedBalances {
+    pub available: u64,
+    /// Whether `available` (from the AES decryptable balance) matches the ElGamal
+    /// available balance that the program debits and proofs are generated against
+    pub available_verified: bool,
+    pub pending: PendingBalance,
+    pub public: u64,
+}
+
+/// The pending balance accumulates one ElGamal credit per incoming deposit or transfer,
+/// so unlike a single transfer amount it can outgrow the 32-bit `decrypt_u32` range.
+#[derive(Clone, Copy)]
+pub enum PendingBalance {
+    Decrypted(u64),
+    /// No amount within the credit counter's bounds matched, e.g. the credits were not
+    /// encrypted to this owner's key
+    Undecryptable,
+}
+
+/// Result document for `--output json`.
+#[derive(Serialize)]
+struct BalanceReport {
+    wallet: String,
+    mint: String,
+    token_account: String,
+    available: u64,
+    available_verified: bool,
+    /// `None` when the pending balance could not be decrypted
+    pending: Option<u64>,
+    public: u64,
+}
 
 pub async fn execute(args: BalanceArgs) -> Result<()> {
     let keypair = load_keypair(&expand_path(&args.keypair))?;
@@ -24,9 +68,53 @@ pub async fn execute(args: BalanceArgs) -> Result<()> {
         .get_account(&ata)
         .with_context(|| format!("Token account not found for wallet {}", args.wallet))?;
 
-    let account_state =
-        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)
-            .context("Failed to unpack token account")?;
+    let balances = decode_balances(&account_data.data, &owner_keys)?;
+
+    status!("Confidential Balance for {}", args.wallet);
+    status!("  Mint: {}", args.mint);
+    status!("  Token Account: {}", ata);
+    status!("  Available Balance: {}", balances.available);
+    if !balances.available_verified {
+        status!("  Warning: the decryptable available balance does not match the encrypted");
+        status!("  available balance. Transfers and withdrawals will fail until it is fixed");
+        status!("  with repair-balance.");
+    }
+
+    match balances.pending {
+        PendingBalance::Decrypted(0) => {}
+        PendingBalance::Decrypted(pending) => {
+            status!("  Pending Balance: {}", pending);
+            status!("  (Use apply-pending to make pending balance available)");
+        }
+        PendingBalance::Undecryptable => {
+            status!("  Pending Balance: could not decrypt");
+        }
+    }
+
+    if balances.public > 0 {
+        status!("  Public Balance: {} (not confidential)", balances.public);
+    }
+
+    output::emit(&BalanceReport {
+        wallet: args.wallet.to_string(),
+        mint: args.mint.to_string(),
+        token_account: ata.to_string(),
+        available: balances.available,
+        available_verified: balances.available_verified,
+        pending: match balances.pending {
+            PendingBalance::Decrypted(pending) => Some(pending),
+            PendingBalance::Undecryptable => None,
+        },
+        public: balances.public,
+    })?;
+
+    Ok(())
+}
+
+/// Unpacks a token account and decrypts its confidential balances with the owner's keys.
+pub fn decode_balances(data: &[u8], owner_keys: &ConfidentialKeys) -> Result<DecodedBalances> {
+    let account_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(data)
+        .context("Failed to unpack token account")?;
 
     let ct_account = account_state
         .get_extension::<ConfidentialTransferAccount>()
@@ -35,40 +123,202 @@ pub async fn execute(args: BalanceArgs) -> Result<()> {
     let decryptable_balance = ct_account.decryptable_available_balance.try_into()
         .map_err(|_| anyhow!("Invalid decryptable balance ciphertext"))?;
 
-    let available_balance = owner_keys
+    let available = owner_keys
         .aes_key
         .decrypt(&decryptable_balance)
         .ok_or_else(|| anyhow!("Failed to decrypt balance - you may not be the owner"))?;
 
+    let available_verified =
+        verify_available_balance(ct_account, &owner_keys.elgamal_keypair, available)?;
+    let pending = decrypt_pending_balance(ct_account, &owner_keys.elgamal_keypair)?;
+
+    Ok(DecodedBalances {
+        available,
+        available_verified,
+        pending,
+        public: account_state.base.amount,
+    })
+}
+
+/// Checks an available balance amount against the ElGamal `available_balance` ciphertext:
+/// the ciphertext minus `available` must encrypt zero, so no discrete log is searched.
+pub fn verify_available_balance(
+    ct_account: &ConfidentialTransferAccount,
+    elgamal_keypair: &ElGamalKeypair,
+    available: u64,
+) -> Result<bool> {
+    let available_balance: ElGamalCiphertext = ct_account
+        .available_balance
+        .try_into()
+        .map_err(|_| anyhow!("Invalid available balance ciphertext"))?;
+
+    let claimed = elgamal_keypair
+        .pubkey()
+        .encrypt_with(available, &PedersenOpening::default());
+    let difference = &available_balance - &claimed;
+    // Decrypting without solving the discrete log leaves amount * G, the identity for zero
+    Ok(elgamal_keypair.secret().decrypt(&difference).target == Default::default())
+}
+
+/// Decrypts the ElGamal `available_balance` ciphertext directly, searching amounts up
+/// to `max_amount`.
+pub fn decrypt_available_balance(
+    ct_account: &ConfidentialTransferAccount,
+    elgamal_keypair: &ElGamalKeypair,
+    max_amount: u64,
+) -> Result<Option<u64>> {
+    let available_balance = ct_account
+        .available_balance
+        .try_into()
+        .map_err(|_| anyhow!("Invalid available balance ciphertext"))?;
+    Ok(decrypt_bounded(elgamal_keypair, &available_balance, max_amount))
+}
+
+/// Decrypts the pending balance. Each credit adds at most 16 bits to the lo part and
+/// 32 bits to the hi part, so the credit counter bounds both searches.
+pub fn decrypt_pending_balance(
+    ct_account: &ConfidentialTransferAccount,
+    elgamal_keypair: &ElGamalKeypair,
+) -> Result<PendingBalance> {
+    let credits = u64::from(ct_account.pending_balance_credit_counter);
+
     let pending_lo = ct_account.pending_balance_lo.try_into()
         .map_err(|_| anyhow!("Invalid pending balance lo ciphertext"))?;
     let pending_hi = ct_account.pending_balance_hi.try_into()
         .map_err(|_| anyhow!("Invalid pending balance hi ciphertext"))?;
 
-    let pending_lo_decrypted = owner_keys.elgamal_keypair.secret().decrypt_u32(&pending_lo);
-    let pending_hi_decrypted = owner_keys.elgamal_keypair.secret().decrypt_u32(&pending_hi);
+    let max_lo = credits.saturating_mul(u16::MAX.into());
+    let max_hi = credits.saturating_mul(u32::MAX.into());
+    let lo = decrypt_bounded(elgamal_keypair, &pending_lo, max_lo);
+    let hi = decrypt_bounded(elgamal_keypair, &pending_hi, max_hi);
 
-    let pending_balance = match (pending_lo_decrypted, pending_hi_decrypted) {
-        (Some(lo), Some(hi)) => Some((hi as u64) << 16 | lo as u64),
+    // The lo part may have carried past 16 bits, so the parts are added rather than or-ed
+    let pending = match (lo, hi) {
+        (Some(lo), Some(hi)) => (hi << 16).checked_add(lo),
         _ => None,
     };
 
-    println!("Confidential Balance for {}", args.wallet);
-    println!("  Mint: {}", args.mint);
-    println!("  Token Account: {}", ata);
-    println!("  Available Balance: {}", available_balance);
+    Ok(pending.map_or(PendingBalance::Undecryptable, PendingBalance::Decrypted))
+}
+
+/// Decrypts a ciphertext whose amount is at most `max_amount`, which may exceed the
+/// 32-bit range of `decrypt_u32`. The amount is searched as `window * 2^32 + x`, one
+/// `decrypt_u32` per 32-bit window, with the windows spread across threads.
+fn decrypt_bounded(
+    elgamal_keypair: &ElGamalKeypair,
+    ciphertext: &ElGamalCiphertext,
+    max_amount: u64,
+) -> Option<u64> {
+    let windows = (max_amount >> 32) + 1;
+    let threads = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(windows as usize);
+    let found = AtomicBool::new(false);
+    // With a zero opening the shift is a bare commitment to `window * 2^32`
+    let zero_opening = PedersenOpening::default();
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads as u64)
+            .map(|first_window| {
+                let found = &found;
+                let zero_opening = &zero_opening;
+                scope.spawn(move || {
+                    for window in (first_window..windows).step_by(threads) {
+                        if found.load(Ordering::Relaxed) {
+                            return None;
+                        }
+                        let shift = elgamal_keypair
+                            .pubkey()
+                            .encrypt_with(window << 32, zero_opening);
+                        let remainder = ciphertext - &shift;
+                        if let Some(x) = remainder.decrypt_u32(elgamal_keypair.secret()) {
+                            found.store(true, Ordering::Relaxed);
+                            return Some((window << 32) + x);
+                        }
+                    }
+                    None
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .find_map(|worker| worker.join().ok().flatten())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An account holding `available` and the pending credits `(lo, hi)`, all encrypted
+    /// to `keypair`.
+    fn account(
+        keypair: &ElGamalKeypair,
+        available: u64,
+        credits: &[(u64, u64)],
+    ) -> ConfidentialTransferAccount {
+        let pubkey = keypair.pubkey();
+        let mut pending_lo = pubkey.encrypt(0_u64);
+        let mut pending_hi = pubkey.encrypt(0_u64);
+        for (lo, hi) in credits {
+            pending_lo = &pending_lo + &pubkey.encrypt(*lo);
+            pending_hi = &pending_hi + &pubkey.encrypt(*hi);
+        }
 
-    if let Some(pending) = pending_balance {
-        if pending > 0 {
-            println!("  Pending Balance: {}", pending);
-            println!("  (Use apply-pending-balance to make pending balance available)");
+        ConfidentialTransferAccount {
+            available_balance: pubkey.encrypt(available).into(),
+            pending_balance_lo: pending_lo.into(),
+            pending_balance_hi: pending_hi.into(),
+            pending_balance_credit_counter: (credits.len() as u64).into(),
+            ..Default::default()
         }
     }
 
-    let public_balance = account_state.base.amount;
-    if public_balance > 0 {
-        println!("  Public Balance: {} (not confidential)", public_balance);
+    #[test]
+    fn decrypts_available_balance_above_u32() {
+        let keypair = ElGamalKeypair::new_rand();
+        let amount = (1 << 32) + 7;
+        let ct_account = account(&keypair, amount, &[]);
+
+        let decrypted = decrypt_available_balance(&ct_account, &keypair, 2 << 32).unwrap();
+        assert_eq!(decrypted, Some(amount));
     }
 
-    Ok(())
+    #[test]
+    fn verifies_available_balance_without_search() {
+        let keypair = ElGamalKeypair::new_rand();
+        let amount = (3 << 32) + 42;
+        let ct_account = account(&keypair, amount, &[]);
+
+        assert!(verify_available_balance(&ct_account, &keypair, amount).unwrap());
+        assert!(!verify_available_balance(&ct_account, &keypair, amount - 1).unwrap());
+        assert!(!verify_available_balance(&ct_account, &keypair, 42).unwrap());
+    }
+
+    #[test]
+    fn decrypts_accumulated_pending_credits() {
+        let keypair = ElGamalKeypair::new_rand();
+        // The lo parts carry past 16 bits and the hi parts past 32 bits
+        let credits = [(0xFFFF, 0x8000_0001), (0xFFFF, 0x8000_0001)];
+        let ct_account = account(&keypair, 0, &credits);
+
+        let expected = credits.iter().map(|(lo, hi)| lo + (hi << 16)).sum::<u64>();
+        match decrypt_pending_balance(&ct_account, &keypair).unwrap() {
+            PendingBalance::Decrypted(pending) => assert_eq!(pending, expected),
+            PendingBalance::Undecryptable => panic!("pending balance did not decrypt"),
+        }
+    }
+
+    #[test]
+    fn pending_credits_for_another_key_are_undecryptable() {
+        let owner = ElGamalKeypair::new_rand();
+        let other = ElGamalKeypair::new_rand();
+        let ct_account = account(&other, 0, &[(1_000, 2_000)]);
+
+        assert!(matches!(
+            decrypt_pending_balance(&ct_account, &owner).unwrap(),
+            PendingBalance::Undecryptable
+        ));
+    }
 }
@@ -0,0 +1,205 @@
+use crate::cli::BurnArgs;
+use crate::compute_budget;
+use crate::config::{create_rpc_client, expand_path, load_keypair};
+use crate::crypto::ConfidentialKeys;
+use anyhow::{anyhow, Context, Result};
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::signature::{Keypair, Signer};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::{
+    extension::{
+        confidential_mint_burn::{
+            account_info::BurnAccountInfo,
+            instruction::{apply_pending_burn, confidential_burn_with_split_proofs},
+            ConfidentialMintBurn,
+        },
+        confidential_transfer::{ConfidentialTransferAccount, ConfidentialTransferMint},
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    solana_zk_sdk::encryption::{elgamal::ElGamalPubkey, pod::elgamal::PodElGamalPubkey},
+};
+use spl_token_client::{
+    client::{ProgramRpcClient, ProgramRpcClientSendTransaction},
+    token::Token,
+};
+use spl_token_confidential_transfer_proof_extraction::instruction::ProofLocation;
+use std::sync::Arc;
+
+pub async fn execute(args: BurnArgs) -> Result<()> {
+    let keypair = load_keypair(&expand_path(&args.keypair))?;
+    let owner_keys = ConfidentialKeys::derive_from_keypair(&keypair)?;
+    let rpc = create_rpc_client(&args.rpc);
+
+    let ata = get_associated_token_address_with_program_id(
+        &keypair.pubkey(),
+        &args.mint,
+        &spl_token_2022::id(),
+    );
+
+    status!("Burning from confidential balance...");
+    status!("  Mint: {}", args.mint);
+    status!("  Token Account: {}", ata);
+    status!("  Amount: {}", args.amount);
+
+    let mint_account_data = rpc.get_account(&args.mint)?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_account_data.data)?;
+    let mint_burn = mint_state
+        .get_extension::<ConfidentialMintBurn>()
+        .map_err(|_| anyhow!("Mint {} does not use confidential mint/burn", args.mint))?;
+    let supply_elgamal_pubkey: ElGamalPubkey = mint_burn.supply_elgamal_pubkey.try_into()
+        .map_err(|_| anyhow!("Invalid supply ElGamal pubkey"))?;
+    let ct_mint = mint_state.get_extension::<ConfidentialTransferMint>()?;
+    let auditor_elgamal_pubkey: Option<ElGamalPubkey> =
+        Option::<PodElGamalPubkey>::from(ct_mint.auditor_elgamal_pubkey)
+            .and_then(|p| ElGamalPubkey::try_from(p).ok());
+    let is_mint_authority = mint_state.base.mint_authority.contains(&keypair.pubkey());
+
+    let account_data = rpc
+        .get_account(&ata)
+        .with_context(|| format!("Token account not found for wallet {}", keypair.pubkey()))?;
+    let account_state =
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)
+            .context("Failed to unpack token account")?;
+    let ct_account = account_state
+        .get_extension::<ConfidentialTransferAccount>()
+        .context("Confidential transfer extension not found on account")?;
+
+    let burn_info = BurnAccountInfo::new(ct_account);
+
+    status!("  Generating proofs...");
+    let proof_data = burn_info
+        .generate_split_burn_proof_data(
+            args.amount,
+            &owner_keys.elgamal_keypair,
+            &owner_keys.aes_key,
+            &supply_elgamal_pubkey,
+            auditor_elgamal_pubkey.as_ref(),
+        )
+        .map_err(|e| anyhow!("Failed to generate burn proof data: {}", e))?;
+    let new_decryptable_balance = burn_info
+        .new_decryptable_balance(args.amount, &owner_keys.aes_key)
+        .map_err(|_| anyhow!("Insufficient confidential balance for burn"))?;
+
+    let rpc_client = Arc::new(NonblockingRpcClient::new_with_commitment(
+        args.rpc.clone(),
+        CommitmentConfig::confirmed(),
+    ));
+
+    let program_client = Arc::new(ProgramRpcClient::new(
+        rpc_client.clone(),
+        ProgramRpcClientSendTransaction,
+    ));
+
+    let token = compute_budget::budgeted_token(
+        Token::new(
+            program_client,
+            &spl_token_2022::id(),
+            &args.mint,
+            Some(mint_state.base.decimals),
+            Arc::new(keypair.insecure_clone()),
+        ),
+        &rpc_client,
+        &[ata],
+    )
+    .await?;
+
+    let equality_proof_keypair = Keypair::new();
+    let ciphertext_validity_proof_keypair = Keypair::new();
+    let range_proof_keypair = Keypair::new();
+
+    status!("  Creating proof context accounts...");
+
+    token.confidential_transfer_create_context_state_account(
+        &equality_proof_keypair.pubkey(),
+        &keypair.pubkey(),
+        &proof_data.equality_proof_data,
+        false,
+        &[&keypair, &equality_proof_keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to create equality proof account: {}", e))?;
+
+    token.confidential_transfer_create_context_state_account(
+        &ciphertext_validity_proof_keypair.pubkey(),
+        &keypair.pubkey(),
+        &proof_data.ciphertext_validity_proof_data_with_ciphertext.proof_data,
+        true, // split account creation and proof verification
+        &[&keypair, &ciphertext_validity_proof_keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to create ciphertext validity proof account: {}", e))?;
+
+    token.confidential_transfer_create_context_state_account(
+        &range_proof_keypair.pubkey(),
+        &keypair.pubkey(),
+        &proof_data.range_proof_data,
+        true, // split account creation and proof verification
+        &[&keypair, &range_proof_keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to create range proof account: {}", e))?;
+
+    status!("  Executing burn...");
+    let mut burn_ixs = confidential_burn_with_split_proofs(
+        &spl_token_2022::id(),
+        &ata,
+        &args.mint,
+        &new_decryptable_balance.into(),
+        &proof_data.ciphertext_validity_proof_data_with_ciphertext.ciphertext_lo,
+        &proof_data.ciphertext_validity_proof_data_with_ciphertext.ciphertext_hi,
+        &keypair.pubkey(),
+        &[],
+        ProofLocation::ContextStateAccount(&equality_proof_keypair.pubkey()),
+        ProofLocation::ContextStateAccount(&ciphertext_validity_proof_keypair.pubkey()),
+        ProofLocation::ContextStateAccount(&range_proof_keypair.pubkey()),
+    )?;
+
+    // Burns accumulate in the mint's pending burn until the mint authority folds them
+    // into the encrypted supply
+    if is_mint_authority {
+        burn_ixs.push(apply_pending_burn(
+            &spl_token_2022::id(),
+            &args.mint,
+            &keypair.pubkey(),
+            &[],
+        )?);
+    }
+
+    let tx = compute_budget::signed_transaction(
+        &rpc,
+        &burn_ixs,
+        &keypair.pubkey(),
+        &[&keypair],
+    )?;
+
+    let signature = rpc.send_and_confirm_transaction_with_spinner(&tx)
+        .context("Failed to burn confidential balance")?;
+
+    // Close context state accounts to recover rent
+    status!("  Cleaning up proof accounts...");
+    for proof_account in [
+        &equality_proof_keypair,
+        &ciphertext_validity_proof_keypair,
+        &range_proof_keypair,
+    ] {
+        token.confidential_transfer_close_context_state_account(
+            &proof_account.pubkey(),
+            &ata,
+            &keypair.pubkey(),
+            &[&keypair],
+        ).await
+        .map_err(|e| anyhow!("Failed to close proof account: {}", e))?;
+    }
+
+    let remaining = owner_keys
+        .aes_key
+        .decrypt(&new_decryptable_balance)
+        .ok_or_else(|| anyhow!("Failed to decrypt remaining balance"))?;
+
+    status!("\nBurn complete.");
+    status!("Signature: {}", signature);
+    status!("Remaining confidential balance: {}", remaining);
+    if !is_mint_authority {
+        status!("(Burn is pending until the mint authority applies pending burns)");
+    }
+
+    Ok(())
+}
@@ -1,38 +1,63 @@
 use crate::cli::ConfigureArgs;
+use crate::compute_budget;
 use crate::config::{expand_path, load_keypair};
 use crate::crypto::ConfidentialKeys;
+use crate::dry_run::DryRun;
+use crate::output;
+use crate::receipt::{response_signature, StepSignature};
 use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
 use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
 use solana_client::rpc_client::RpcClient;
 use solana_commitment_config::CommitmentConfig;
 use solana_sdk::{
-    signature::{Keypair, Signer},
-    transaction::Transaction,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
 };
 use spl_associated_token_account::{
     get_associated_token_address_with_program_id,
     instruction::create_associated_token_account,
 };
 use spl_token_2022::{
-    extension::ExtensionType,
+    extension::{
+        confidential_transfer::instruction::configure_account, transfer_fee::TransferFeeConfig,
+        BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
     instruction::reallocate,
+    solana_zk_sdk::zk_elgamal_proof_program::proof_data::PubkeyValidityProofData,
+    state::{Account, Mint},
 };
 use spl_token_client::{
     client::{ProgramRpcClient, ProgramRpcClientSendTransaction},
     token::Token,
 };
+use spl_token_confidential_transfer_proof_extraction::instruction::{ProofData, ProofLocation};
 use std::sync::Arc;
 
-pub async fn execute(args: ConfigureArgs) -> Result<()> {
+/// What the `Token` client uses when no maximum is given to `ConfigureAccount`.
+const DEFAULT_MAXIMUM_PENDING_BALANCE_CREDIT_COUNTER: u64 = 65536;
+
+/// Result document for `--output json`.
+#[derive(Serialize)]
+struct ConfigureReport {
+    mint: String,
+    owner: String,
+    token_account: String,
+    created_token_account: bool,
+    signatures: Vec<StepSignature>,
+}
+
+pub async fn execute(args: ConfigureArgs, dry_run: bool) -> Result<()> {
     let owner = load_keypair(&expand_path(&args.owner))?;
     let fee_payer = match &args.fee_payer {
         Some(path) => load_keypair(&expand_path(path))?,
         None => owner.insecure_clone(),
     };
 
-    println!("Configuring confidential transfer account...");
-    println!("  Mint: {}", args.mint);
-    println!("  Owner: {}", owner.pubkey());
+    status!("Configuring confidential transfer account...");
+    status!("  Mint: {}", args.mint);
+    status!("  Owner: {}", owner.pubkey());
 
     let owner_keys = ConfidentialKeys::derive_from_keypair(&owner)?;
 
@@ -44,12 +69,39 @@ pub async fn execute(args: ConfigureArgs) -> Result<()> {
         &spl_token_2022::id(),
     );
 
+    if dry_run {
+        let instructions = configure_instructions(
+            &rpc,
+            &args.mint,
+            &owner.pubkey(),
+            &fee_payer.pubkey(),
+            &owner_keys,
+        )?;
+        let signers: Vec<&Keypair> = if fee_payer.pubkey() == owner.pubkey() {
+            vec![&owner]
+        } else {
+            vec![&fee_payer, &owner]
+        };
+
+        let mut simulation = DryRun::default();
+        simulation.simulate(
+            &rpc,
+            "configure_token_account",
+            &instructions,
+            &fee_payer.pubkey(),
+            &signers,
+        )?;
+        return simulation.finish();
+    }
+
+    let mut signatures = Vec::new();
+
     // Check if ATA exists
     let ata_exists = rpc.get_account(&ata).is_ok();
 
     if !ata_exists {
         // Create the ATA
-        println!("Creating token account...");
+        status!("Creating token account...");
         let create_ata_ix = create_associated_token_account(
             &fee_payer.pubkey(),
             &owner.pubkey(),
@@ -57,49 +109,50 @@ pub async fn execute(args: ConfigureArgs) -> Result<()> {
             &spl_token_2022::id(),
         );
 
-        let recent_blockhash = rpc.get_latest_blockhash()?;
-        let tx = Transaction::new_signed_with_payer(
+        let tx = compute_budget::signed_transaction(
+            &rpc,
             &[create_ata_ix],
-            Some(&fee_payer.pubkey()),
+            &fee_payer.pubkey(),
             &[&fee_payer],
-            recent_blockhash,
-        );
+        )?;
 
-        rpc.send_and_confirm_transaction_with_spinner(&tx)
+        let signature = rpc.send_and_confirm_transaction_with_spinner(&tx)
             .context("Failed to create token account")?;
+        signatures.push(step_signature("create_token_account", signature));
     } else {
-        println!("Token account already exists");
+        status!("Token account already exists");
     }
 
     // Reallocate for confidential transfer extension
-    println!("Reallocating for confidential transfer...");
+    status!("Reallocating for confidential transfer...");
+    let account_data = rpc.get_account(&ata)?;
     let reallocate_ix = reallocate(
         &spl_token_2022::id(),
         &ata,
         &fee_payer.pubkey(),
         &owner.pubkey(),
         &[&owner.pubkey()],
-        &[ExtensionType::ConfidentialTransferAccount],
+        &confidential_account_extensions(&account_data.data)?,
     )?;
 
-    let recent_blockhash = rpc.get_latest_blockhash()?;
     let signers: Vec<&Keypair> = if fee_payer.pubkey() == owner.pubkey() {
         vec![&owner]
     } else {
         vec![&fee_payer, &owner]
     };
-    let tx = Transaction::new_signed_with_payer(
+    let tx = compute_budget::signed_transaction(
+        &rpc,
         &[reallocate_ix],
-        Some(&fee_payer.pubkey()),
+        &fee_payer.pubkey(),
         &signers,
-        recent_blockhash,
-    );
+    )?;
 
-    rpc.send_and_confirm_transaction_with_spinner(&tx)
+    let signature = rpc.send_and_confirm_transaction_with_spinner(&tx)
         .context("Failed to reallocate token account for confidential transfer")?;
+    signatures.push(step_signature("reallocate_token_account", signature));
 
     // Use Token client to configure the account - it handles proof generation
-    println!("Configuring confidential transfer...");
+    status!("Configuring confidential transfer...");
     let rpc_client = Arc::new(NonblockingRpcClient::new_with_commitment(
         args.rpc.clone(),
         CommitmentConfig::confirmed(),
@@ -110,15 +163,20 @@ pub async fn execute(args: ConfigureArgs) -> Result<()> {
         ProgramRpcClientSendTransaction,
     ));
 
-    let token = Token::new(
-        program_client,
-        &spl_token_2022::id(),
-        &args.mint,
-        None,
-        Arc::new(fee_payer.insecure_clone()),
-    );
+    let token = compute_budget::budgeted_token(
+        Token::new(
+            program_client,
+            &spl_token_2022::id(),
+            &args.mint,
+            None,
+            Arc::new(fee_payer.insecure_clone()),
+        ),
+        &rpc_client,
+        &[ata],
+    )
+    .await?;
 
-    token
+    let response = token
         .confidential_transfer_configure_token_account(
             &ata,
             &owner.pubkey(),
@@ -130,10 +188,101 @@ pub async fn execute(args: ConfigureArgs) -> Result<()> {
         )
         .await
         .map_err(|e| anyhow!("Failed to configure confidential transfer account: {}", e))?;
+    if let Some(signature) = response_signature(response) {
+        signatures.push(step_signature("configure_token_account", signature));
+    }
+
+    status!("\nConfiguration complete.");
+    status!("Token account: {}", ata);
+    status!("Owner: {}", owner.pubkey());
 
-    println!("\nConfiguration complete.");
-    println!("Token account: {}", ata);
-    println!("Owner: {}", owner.pubkey());
+    output::emit(&ConfigureReport {
+        mint: args.mint.to_string(),
+        owner: owner.pubkey().to_string(),
+        token_account: ata.to_string(),
+        created_token_account: !ata_exists,
+        signatures,
+    })?;
 
     Ok(())
 }
+
+fn step_signature(step: &str, signature: Signature) -> StepSignature {
+    StepSignature {
+        step: step.to_string(),
+        signature: signature.to_string(),
+    }
+}
+
+/// Everything `configure` sends, as one transaction for `--dry-run`: creating the ATA if
+/// needed, reallocating, and `ConfigureAccount` with its pubkey validity proof verified
+/// in the same transaction instead of through the `Token` client.
+pub fn configure_instructions(
+    rpc: &RpcClient,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    fee_payer: &Pubkey,
+    owner_keys: &ConfidentialKeys,
+) -> Result<Vec<Instruction>> {
+    let ata = get_associated_token_address_with_program_id(owner, mint, &spl_token_2022::id());
+
+    let mut instructions = Vec::new();
+    let extensions = match rpc.get_account(&ata) {
+        Ok(account) => confidential_account_extensions(&account.data)?,
+        Err(_) => {
+            instructions.push(create_associated_token_account(
+                fee_payer,
+                owner,
+                mint,
+                &spl_token_2022::id(),
+            ));
+
+            // A new ATA on a fee-bearing mint starts with TransferFeeAmount
+            let mint_account = rpc.get_account(mint)?;
+            let mint_state = StateWithExtensions::<Mint>::unpack(&mint_account.data)
+                .context("Failed to unpack mint")?;
+            let mut extensions = vec![ExtensionType::ConfidentialTransferAccount];
+            if mint_state.get_extension::<TransferFeeConfig>().is_ok() {
+                extensions.push(ExtensionType::ConfidentialTransferFeeAmount);
+            }
+            extensions
+        }
+    };
+
+    instructions.push(reallocate(
+        &spl_token_2022::id(),
+        &ata,
+        fee_payer,
+        owner,
+        &[owner],
+        &extensions,
+    )?);
+
+    let proof_data = PubkeyValidityProofData::new(&owner_keys.elgamal_keypair)
+        .map_err(|e| anyhow!("Failed to generate pubkey validity proof: {}", e))?;
+    instructions.extend(configure_account(
+        &spl_token_2022::id(),
+        &ata,
+        mint,
+        &owner_keys.aes_key.encrypt(0).into(),
+        DEFAULT_MAXIMUM_PENDING_BALANCE_CREDIT_COUNTER,
+        owner,
+        &[],
+        ProofLocation::InstructionOffset(1i8.try_into()?, ProofData::InstructionData(&proof_data)),
+    )?);
+
+    Ok(instructions)
+}
+
+/// Extensions a token account needs room for before `ConfigureAccount`. Accounts on
+/// fee-bearing mints (which carry `TransferFeeAmount`) also get a confidential withheld fee.
+pub fn confidential_account_extensions(account_data: &[u8]) -> Result<Vec<ExtensionType>> {
+    let account = StateWithExtensions::<Account>::unpack(account_data)
+        .context("Failed to unpack token account")?;
+
+    let mut extensions = vec![ExtensionType::ConfidentialTransferAccount];
+    if account.get_extension_types()?.contains(&ExtensionType::TransferFeeAmount) {
+        extensions.push(ExtensionType::ConfidentialTransferFeeAmount);
+    }
+    Ok(extensions)
+}
@@ -1,15 +1,22 @@
 use crate::cli::CreateArgs;
-use crate::config::{create_rpc_client, expand_path, load_keypair};
+use crate::commands::configure::{confidential_account_extensions, configure_instructions};
+use crate::compute_budget;
+use crate::config::{create_rpc_client, expand_path, load_keypair, save_keypair};
 use crate::crypto::{parse_elgamal_pubkey, ConfidentialKeys};
+use crate::dry_run::DryRun;
+use crate::error::StealthLaunchError;
+use crate::grind::grind_keypair;
+use crate::output;
+use crate::receipt::LaunchReceipt;
 use anyhow::{anyhow, Context, Result};
 use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
 use solana_client::rpc_client::RpcClient;
 use solana_commitment_config::CommitmentConfig;
 #[allow(deprecated)]
 use solana_sdk::{
+    instruction::Instruction,
     pubkey::Pubkey,
     signature::{Keypair, Signer},
-    transaction::Transaction,
 };
 use solana_system_interface::instruction as system_instruction;
 use spl_associated_token_account::{
@@ -18,10 +25,23 @@ use spl_associated_token_account::{
 };
 use spl_token_2022::{
     extension::{
-        confidential_transfer::instruction::initialize_mint as init_ct_mint,
-        ExtensionType,
+        confidential_mint_burn::{
+            account_info::SupplyAccountInfo,
+            instruction::{
+                confidential_mint_with_split_proofs, initialize_mint as init_mint_burn,
+            },
+            ConfidentialMintBurn,
+        },
+        confidential_transfer::{
+            instruction::{approve_account, deposit, initialize_mint as init_ct_mint},
+            ConfidentialTransferAccount, ConfidentialTransferMint,
+        },
+        confidential_transfer_fee::instruction::initialize_confidential_transfer_fee_config,
+        metadata_pointer::instruction::initialize as init_metadata_pointer,
+        transfer_fee::{instruction::initialize_transfer_fee_config, MAX_FEE_BASIS_POINTS},
+        BaseStateWithExtensions, ExtensionType, StateWithExtensions,
     },
-    instruction::{initialize_mint, reallocate},
+    instruction::{initialize_mint, mint_to, reallocate},
     solana_zk_sdk::encryption::{
         elgamal::ElGamalPubkey,
         pod::elgamal::PodElGamalPubkey,
@@ -32,37 +52,81 @@ use spl_token_client::{
     client::{ProgramRpcClient, ProgramRpcClientSendTransaction},
     token::Token,
 };
+use spl_token_confidential_transfer_proof_extraction::instruction::ProofLocation;
+use spl_token_metadata_interface::{
+    instruction::initialize as init_token_metadata,
+    state::TokenMetadata,
+};
+use std::fs;
 use std::sync::Arc;
 
-pub async fn execute(args: CreateArgs) -> Result<()> {
+pub async fn execute(args: CreateArgs, dry_run: bool) -> Result<()> {
     let keypair = load_keypair(&expand_path(&args.keypair))?;
     let rpc = create_rpc_client(&args.rpc);
 
-    println!("Creating confidential token mint...");
-    println!("  Name: {}", args.name);
-    println!("  Symbol: {}", args.symbol);
-    println!("  Supply: {} (hidden)", args.supply);
-    println!("  Decimals: {}", args.decimals);
+    status!("Creating confidential token mint...");
+    status!("  Name: {}", args.name);
+    status!("  Symbol: {}", args.symbol);
+    if !args.uri.is_empty() {
+        status!("  URI: {}", args.uri);
+    }
+    if args.confidential_supply {
+        status!("  Supply: {} (confidential)", args.supply);
+    } else {
+        status!("  Supply: {} (public mint, then deposited)", args.supply);
+    }
+    status!("  Decimals: {}", args.decimals);
+    if args.require_approval {
+        status!("  Account approval: manual");
+    }
 
     let auditor_elgamal_pubkey = match &args.auditor {
         Some(s) => Some(parse_elgamal_pubkey(s)?),
         None => None,
     };
 
-    let mint_keypair = Keypair::new();
-    let mint_pubkey = mint_keypair.pubkey();
-
-    let authority_keys = ConfidentialKeys::derive_from_keypair(&keypair)?;
+    let fee = match (args.fee_basis_points, args.max_fee) {
+        (Some(basis_points), Some(maximum_fee)) => {
+            if basis_points > MAX_FEE_BASIS_POINTS {
+                return Err(anyhow!(
+                    "--fee-basis-points must be at most {}",
+                    MAX_FEE_BASIS_POINTS
+                ));
+            }
+            status!("  Transfer fee: {} bps (max {})", basis_points, maximum_fee);
+            Some((basis_points, maximum_fee))
+        }
+        _ => None,
+    };
 
-    create_confidential_mint(
-        &rpc,
-        &keypair,
-        &mint_keypair,
-        auditor_elgamal_pubkey.as_ref(),
-        args.decimals,
-    )?;
+    let mint_keypair = if let Some(path) = &args.mint_keypair {
+        Some(load_keypair(&expand_path(path))?)
+    } else if args.grind_prefix.is_some() || args.grind_suffix.is_some() {
+        Some(grind_keypair(args.grind_prefix.as_deref(), args.grind_suffix.as_deref())?)
+    } else if args.resume.is_none() {
+        Some(Keypair::new())
+    } else {
+        None
+    };
+    let mint_pubkey = match (args.resume, &mint_keypair) {
+        (Some(mint), Some(kp)) if kp.pubkey() != mint => {
+            return Err(anyhow!(
+                "--mint-keypair {} does not match --resume mint {}",
+                kp.pubkey(),
+                mint
+            ));
+        }
+        (Some(mint), _) => mint,
+        (None, Some(kp)) => kp.pubkey(),
+        (None, None) => unreachable!("a mint keypair is always generated without --resume"),
+    };
 
-    println!("Mint created: {}", mint_pubkey);
+    let authority_keys = ConfidentialKeys::derive_from_keypair(&keypair)?;
+    let transfer_fee = fee.map(|(basis_points, maximum_fee)| TransferFeeSettings {
+        basis_points,
+        maximum_fee,
+        withdraw_withheld_keys: &authority_keys,
+    });
 
     let ata = get_associated_token_address_with_program_id(
         &keypair.pubkey(),
@@ -70,96 +134,585 @@ pub async fn execute(args: CreateArgs) -> Result<()> {
         &spl_token_2022::id(),
     );
 
-    create_and_configure_ata(&args.rpc, &keypair, &mint_pubkey, &authority_keys).await?;
-    println!("Token account created and configured: {}", ata);
+    // A dry run leaves no files behind: no saved mint keypair and no receipt
+    let out_dir = match &args.out_dir {
+        Some(dir) if !dry_run => {
+            let dir = expand_path(dir);
+            fs::create_dir_all(&dir)
+                .with_context(|| format!("Failed to create output directory {}", dir.display()))?;
+            Some(dir)
+        }
+        _ => None,
+    };
 
-    if args.supply > 0 {
-        mint_and_deposit(
-            &args.rpc,
+    // Save the mint keypair before anything touches the chain so a failed launch can
+    // still be inspected or finished with the same address.
+    let mint_keypair_path = match (&out_dir, &mint_keypair, args.save_mint_keypair) {
+        (Some(dir), Some(kp), true) => {
+            let path = dir.join(format!("{}.json", mint_pubkey));
+            // A resumed launch saves the same mint keypair again
+            save_keypair(kp, &path, true)?;
+            status!("  Mint keypair saved: {}", path.display());
+            Some(path)
+        }
+        _ => None,
+    };
+
+    let receipt_path = out_dir.map(|dir| dir.join(format!("{}-receipt.json", mint_pubkey)));
+    let mut receipt = match &receipt_path {
+        Some(path) if args.resume.is_some() && path.exists() => {
+            status!("  Continuing receipt: {}", path.display());
+            LaunchReceipt::load(path)?
+        }
+        _ => LaunchReceipt {
+            name: args.name.clone(),
+            symbol: args.symbol.clone(),
+            uri: args.uri.clone(),
+            mint: mint_pubkey.to_string(),
+            authority: keypair.pubkey().to_string(),
+            decimals: args.decimals,
+            supply: args.supply,
+            auditor_elgamal_pubkey: args.auditor.clone(),
+            token_account: ata.to_string(),
+            mint_keypair_path: mint_keypair_path.map(|p| p.display().to_string()),
+            signatures: Vec::new(),
+            complete: false,
+            path: None,
+        },
+    };
+    // Written by the first recorded step, so a run that fails before changing anything
+    // leaves no receipt behind
+    receipt.path = receipt_path;
+
+    let mint_exists = rpc.get_account(&mint_pubkey).is_ok();
+    if mint_exists {
+        if args.resume.is_none() {
+            return Err(anyhow!(
+                "Mint {} already exists - use --resume {} to finish the launch",
+                mint_pubkey,
+                mint_pubkey
+            ));
+        }
+        status!("Mint exists: {} (skipping creation)", mint_pubkey);
+    } else {
+        let mint_keypair = mint_keypair.as_ref().ok_or_else(|| {
+            anyhow!(
+                "Mint {} not found on-chain - pass --mint-keypair to create it",
+                mint_pubkey
+            )
+        })?;
+
+        let instructions = create_mint_instructions(
+            &rpc,
+            &keypair.pubkey(),
+            &mint_keypair.pubkey(),
+            auditor_elgamal_pubkey.as_ref(),
+            args.decimals,
+            &args.name,
+            &args.symbol,
+            &args.uri,
+            !args.require_approval,
+            args.confidential_supply.then_some(&authority_keys),
+            transfer_fee.as_ref(),
+        )?;
+
+        if dry_run {
+            let mut simulation = DryRun::default();
+            simulation.simulate(
+                &rpc,
+                "create_mint",
+                &instructions,
+                &keypair.pubkey(),
+                &[&keypair, mint_keypair],
+            )?;
+            for step in launch_steps_after_mint(&args) {
+                simulation.skip(step, "requires the mint, which a dry run does not create");
+            }
+            return simulation.finish();
+        }
+
+        let tx = compute_budget::signed_transaction(
+            &rpc,
+            &instructions,
+            &keypair.pubkey(),
+            &[&keypair, mint_keypair],
+        )?;
+
+        let signature = rpc.send_and_confirm_transaction_with_spinner(&tx)
+            .context("Failed to create mint")?;
+        receipt.record("create_mint", signature)?;
+
+        status!("Mint created: {}", mint_pubkey);
+    }
+
+    let mint_account_data = rpc.get_account(&mint_pubkey)?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_account_data.data)
+        .context("Failed to unpack mint")?;
+    mint_state
+        .get_extension::<ConfidentialTransferMint>()
+        .map_err(|_| StealthLaunchError::ConfidentialTransferNotConfigured)?;
+    let decimals = mint_state.base.decimals;
+    if decimals != args.decimals {
+        status!("  Using on-chain decimals: {}", decimals);
+    }
+
+    let rpc_client = Arc::new(NonblockingRpcClient::new_with_commitment(
+        args.rpc.clone(),
+        CommitmentConfig::confirmed(),
+    ));
+
+    let program_client = Arc::new(ProgramRpcClient::new(
+        rpc_client.clone(),
+        ProgramRpcClientSendTransaction,
+    ));
+
+    let token = compute_budget::budgeted_token(
+        Token::new(
+            program_client,
+            &spl_token_2022::id(),
+            &mint_pubkey,
+            Some(decimals),
+            Arc::new(keypair.insecure_clone()),
+        ),
+        &rpc_client,
+        &[ata],
+    )
+    .await?;
+
+    let state = fetch_launch_state(&rpc, &mint_pubkey, &ata, &authority_keys)?;
+
+    if dry_run {
+        return dry_run_resumed_launch(
+            &rpc,
+            &args,
             &keypair,
             &mint_pubkey,
             &ata,
             &authority_keys,
-            args.supply,
-            args.decimals,
-        ).await?;
-        println!("Minted and deposited {} tokens to confidential balance", args.supply);
+            &state,
+        );
     }
 
-    println!("\nToken launch complete.");
-    println!("Mint address: {}", mint_pubkey);
-    println!("Authority: {}", keypair.pubkey());
+    if state.ata_exists {
+        status!("Token account exists: {} (skipping creation)", ata);
+    } else {
+        create_ata(&rpc, &keypair, &mint_pubkey, &mut receipt)?;
+        status!("Token account created: {}", ata);
+    }
+
+    if state.ct_configured {
+        status!("Token account already configured for confidential transfers");
+    } else {
+        configure_ata(&rpc, &token, &keypair, &ata, &authority_keys, &mut receipt).await?;
+        status!("Token account configured for confidential transfers");
+    }
+
+    let state = fetch_launch_state(&rpc, &mint_pubkey, &ata, &authority_keys)?;
+
+    if !state.ct_approved {
+        let response = token
+            .confidential_transfer_approve_account(&ata, &keypair.pubkey(), &[&keypair])
+            .await
+            .map_err(|e| anyhow!("Failed to approve token account: {}", e))?;
+        receipt.record_response("approve_account", response)?;
+        status!("Token account approved");
+    }
+
+    if args.supply > 0 && state.supply == 0 {
+        if state.confidential_supply {
+            confidential_mint(
+                &rpc,
+                &token,
+                &keypair,
+                &mint_pubkey,
+                &ata,
+                &authority_keys,
+                args.supply,
+                &mut receipt,
+            ).await?;
+            status!("Minted {} tokens confidentially", args.supply);
+        } else {
+            let response = token
+                .mint_to(&ata, &keypair.pubkey(), args.supply, &[&keypair])
+                .await
+                .map_err(|e| anyhow!("Failed to mint tokens: {}", e))?;
+            receipt.record_response("mint_to", response)?;
+            status!("Minted {} tokens", args.supply);
+        }
+    } else if state.supply > 0 {
+        status!("Supply already minted (skipping)");
+    }
+
+    let state = fetch_launch_state(&rpc, &mint_pubkey, &ata, &authority_keys)?;
+
+    if state.public_balance > 0 {
+        let response = token
+            .confidential_transfer_deposit(
+                &ata,
+                &keypair.pubkey(),
+                state.public_balance,
+                decimals,
+                &[&keypair],
+            )
+            .await
+            .map_err(|e| anyhow!("Failed to deposit to confidential balance: {}", e))?;
+        receipt.record_response("deposit", response)?;
+        status!("Deposited {} tokens to confidential balance", state.public_balance);
+    }
+
+    let state = fetch_launch_state(&rpc, &mint_pubkey, &ata, &authority_keys)?;
+
+    if state.pending_credits > 0 {
+        let response = token
+            .confidential_transfer_apply_pending_balance(
+                &ata,
+                &keypair.pubkey(),
+                None,
+                authority_keys.elgamal_keypair.secret(),
+                &authority_keys.aes_key,
+                &[&keypair],
+            )
+            .await
+            .map_err(|e| anyhow!("Failed to apply pending balance: {}", e))?;
+        receipt.record_response("apply_pending_balance", response)?;
+        status!("Applied pending balance");
+    }
+
+    receipt.complete = true;
+    receipt.save()?;
+
+    status!("\nToken launch complete.");
+    status!("Mint address: {}", mint_pubkey);
+    status!("Token: {} ({})", args.name, args.symbol);
+    status!("Authority: {}", keypair.pubkey());
+    if let Some(path) = &receipt.path {
+        status!("Receipt: {}", path.display());
+    }
+
+    // The receipt already records the addresses and every step's signature
+    output::emit(&receipt)?;
 
     Ok(())
 }
 
-fn create_confidential_mint(
+/// On-chain progress of a launch, used to skip steps that already landed.
+struct LaunchState {
+    ata_exists: bool,
+    ct_configured: bool,
+    ct_approved: bool,
+    /// Whether the mint uses `ConfidentialMintBurn`, in which case `supply` is decrypted
+    confidential_supply: bool,
+    supply: u64,
+    public_balance: u64,
+    pending_credits: u64,
+}
+
+fn fetch_launch_state(
     rpc: &RpcClient,
-    payer: &Keypair,
-    mint_keypair: &Keypair,
-    auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
-    decimals: u8,
+    mint: &Pubkey,
+    ata: &Pubkey,
+    authority_keys: &ConfidentialKeys,
+) -> Result<LaunchState> {
+    let mint_account_data = rpc.get_account(mint)?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_account_data.data)
+        .context("Failed to unpack mint")?;
+    let (confidential_supply, supply) = match mint_state.get_extension::<ConfidentialMintBurn>() {
+        Ok(mint_burn) => (
+            true,
+            SupplyAccountInfo::new(mint_burn)
+                .decrypted_current_supply(&authority_keys.aes_key, &authority_keys.elgamal_keypair)
+                .map_err(|e| anyhow!("Failed to decrypt confidential supply: {}", e))?,
+        ),
+        Err(_) => (false, mint_state.base.supply),
+    };
+
+    let Ok(account_data) = rpc.get_account(ata) else {
+        return Ok(LaunchState {
+            ata_exists: false,
+            ct_configured: false,
+            ct_approved: false,
+            confidential_supply,
+            supply,
+            public_balance: 0,
+            pending_credits: 0,
+        });
+    };
+
+    let account_state =
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)
+            .context("Failed to unpack token account")?;
+
+    let (ct_configured, ct_approved, pending_credits) =
+        match account_state.get_extension::<ConfidentialTransferAccount>() {
+            Ok(ct) => (
+                true,
+                bool::from(ct.approved),
+                u64::from(ct.pending_balance_credit_counter),
+            ),
+            Err(_) => (false, false, 0),
+        };
+
+    Ok(LaunchState {
+        ata_exists: true,
+        ct_configured,
+        ct_approved,
+        confidential_supply,
+        supply,
+        public_balance: account_state.base.amount,
+        pending_credits,
+    })
+}
+
+/// Steps a fresh launch runs once the mint exists, listed by a dry run that cannot
+/// simulate them.
+fn launch_steps_after_mint(args: &CreateArgs) -> Vec<&'static str> {
+    let mut steps = vec!["create_token_account", "configure_token_account"];
+    if args.require_approval {
+        steps.push("approve_account");
+    }
+    if args.supply > 0 {
+        if args.confidential_supply {
+            steps.push("confidential_mint");
+        } else {
+            steps.push("mint_to");
+            steps.push("deposit");
+        }
+        steps.push("apply_pending_balance");
+    }
+    steps
+}
+
+/// Dry run of `--resume` on an existing mint: simulates each remaining step whose
+/// prerequisites are already on chain and lists the rest.
+fn dry_run_resumed_launch(
+    rpc: &RpcClient,
+    args: &CreateArgs,
+    authority: &Keypair,
+    mint: &Pubkey,
+    ata: &Pubkey,
+    authority_keys: &ConfidentialKeys,
+    state: &LaunchState,
 ) -> Result<()> {
-    let extensions = vec![ExtensionType::ConfidentialTransferMint];
+    let mint_account_data = rpc.get_account(mint)?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_account_data.data)
+        .context("Failed to unpack mint")?;
+    let auto_approve = bool::from(
+        mint_state
+            .get_extension::<ConfidentialTransferMint>()?
+            .auto_approve_new_accounts,
+    );
+    let decimals = mint_state.base.decimals;
 
-    let space = ExtensionType::try_calculate_account_len::<Mint>(&extensions)?;
-    let rent = rpc.get_minimum_balance_for_rent_exemption(space)?;
+    let payer = authority.pubkey();
+    let signers = [authority];
+    let mut simulation = DryRun::default();
+
+    if !state.ct_configured {
+        let instructions = configure_instructions(rpc, mint, &payer, &payer, authority_keys)?;
+        simulation.simulate(rpc, "configure_token_account", &instructions, &payer, &signers)?;
+    }
+
+    if state.ct_configured && !state.ct_approved {
+        let approve_ix = approve_account(&spl_token_2022::id(), ata, mint, &payer, &[])?;
+        simulation.simulate(rpc, "approve_account", &[approve_ix], &payer, &signers)?;
+    } else if !state.ct_configured && !auto_approve {
+        simulation.skip("approve_account", "requires configure_token_account");
+    }
+
+    let minting = args.supply > 0 && state.supply == 0;
+    if minting {
+        if state.confidential_supply {
+            simulation.skip("confidential_mint", "requires proof context accounts to exist");
+        } else if state.ata_exists {
+            let mint_ix = mint_to(&spl_token_2022::id(), mint, ata, &payer, &[], args.supply)?;
+            simulation.simulate(rpc, "mint_to", &[mint_ix], &payer, &signers)?;
+        } else {
+            simulation.skip("mint_to", "requires the token account");
+        }
+    }
+
+    if state.public_balance > 0 && state.ct_configured {
+        let deposit_ix = deposit(
+            &spl_token_2022::id(),
+            ata,
+            mint,
+            state.public_balance,
+            decimals,
+            &payer,
+            &[],
+        )?;
+        simulation.simulate(rpc, "deposit", &[deposit_ix], &payer, &signers)?;
+    } else if state.public_balance > 0 || (minting && !state.confidential_supply) {
+        simulation.skip("deposit", "requires the steps above to land");
+    }
+
+    if state.pending_credits > 0 || state.public_balance > 0 || minting {
+        simulation.skip(
+            "apply_pending_balance",
+            "its new decryptable balance depends on the credits above",
+        );
+    }
+
+    simulation.finish()
+}
+
+/// Transfer fee for a new mint. Confidentially withheld fees are encrypted to
+/// `withdraw_withheld_keys`, so only that keypair can harvest them.
+struct TransferFeeSettings<'a> {
+    basis_points: u16,
+    maximum_fee: u64,
+    withdraw_withheld_keys: &'a ConfidentialKeys,
+}
+
+/// Builds the single transaction that creates and initializes the mint with all of its
+/// extensions and metadata.
+#[allow(clippy::too_many_arguments)]
+fn create_mint_instructions(
+    rpc: &RpcClient,
+    payer: &Pubkey,
+    mint: &Pubkey,
+    auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
+    decimals: u8,
+    name: &str,
+    symbol: &str,
+    uri: &str,
+    auto_approve_new_accounts: bool,
+    supply_keys: Option<&ConfidentialKeys>,
+    transfer_fee: Option<&TransferFeeSettings>,
+) -> Result<Vec<Instruction>> {
+    // The account is created with room for the fixed-size extensions only; the token
+    // program reallocates when metadata is initialized, so fund rent for both up front.
+    let (space, metadata_space) = mint_account_space(
+        name,
+        symbol,
+        uri,
+        supply_keys.is_some(),
+        transfer_fee.is_some(),
+    )?;
+    let rent = rpc.get_minimum_balance_for_rent_exemption(space + metadata_space)?;
 
     let create_account_ix = system_instruction::create_account(
-        &payer.pubkey(),
-        &mint_keypair.pubkey(),
+        payer,
+        mint,
         rent,
         space as u64,
         &spl_token_2022::id(),
     );
 
+    let init_metadata_pointer_ix = init_metadata_pointer(
+        &spl_token_2022::id(),
+        mint,
+        Some(*payer),
+        Some(*mint),
+    )?;
+
     let auditor_pod: Option<PodElGamalPubkey> = auditor_elgamal_pubkey.map(|p| (*p).into());
 
     let init_ct_ix = init_ct_mint(
         &spl_token_2022::id(),
-        &mint_keypair.pubkey(),
-        Some(payer.pubkey()),
-        true,
+        mint,
+        Some(*payer),
+        auto_approve_new_accounts,
         auditor_pod,
     )?;
 
+    let mut init_ixs = vec![create_account_ix, init_metadata_pointer_ix, init_ct_ix];
+
+    // The supply is encrypted under the authority's ElGamal key and starts at zero
+    if let Some(keys) = supply_keys {
+        let supply_elgamal_pubkey: PodElGamalPubkey = keys.elgamal_pubkey().into();
+        init_ixs.push(init_mint_burn(
+            &spl_token_2022::id(),
+            mint,
+            &supply_elgamal_pubkey,
+            &keys.aes_key.encrypt(0).into(),
+        )?);
+    }
+
+    // The payer holds both fee authorities; withheld fees are encrypted to its ElGamal key
+    if let Some(fee) = transfer_fee {
+        init_ixs.push(initialize_transfer_fee_config(
+            &spl_token_2022::id(),
+            mint,
+            Some(payer),
+            Some(payer),
+            fee.basis_points,
+            fee.maximum_fee,
+        )?);
+
+        let withdraw_withheld_elgamal_pubkey: PodElGamalPubkey =
+            fee.withdraw_withheld_keys.elgamal_pubkey().into();
+        init_ixs.push(initialize_confidential_transfer_fee_config(
+            &spl_token_2022::id(),
+            mint,
+            Some(*payer),
+            &withdraw_withheld_elgamal_pubkey,
+        )?);
+    }
+
     let init_mint_ix = initialize_mint(
         &spl_token_2022::id(),
-        &mint_keypair.pubkey(),
-        &payer.pubkey(),
+        mint,
+        payer,
         None,
         decimals,
     )?;
 
-    let recent_blockhash = rpc.get_latest_blockhash()?;
-    let tx = Transaction::new_signed_with_payer(
-        &[create_account_ix, init_ct_ix, init_mint_ix],
-        Some(&payer.pubkey()),
-        &[payer, mint_keypair],
-        recent_blockhash,
+    let init_metadata_ix = init_token_metadata(
+        &spl_token_2022::id(),
+        mint,
+        payer,
+        mint,
+        payer,
+        name.to_string(),
+        symbol.to_string(),
+        uri.to_string(),
     );
 
-    rpc.send_and_confirm_transaction_with_spinner(&tx)
-        .context("Failed to create mint")?;
+    init_ixs.push(init_mint_ix);
+    init_ixs.push(init_metadata_ix);
 
-    Ok(())
+    Ok(init_ixs)
+}
+
+/// Space of a new mint account with this launch's fixed-size extensions, and the space
+/// its token metadata adds once initialized.
+pub fn mint_account_space(
+    name: &str,
+    symbol: &str,
+    uri: &str,
+    confidential_supply: bool,
+    transfer_fee: bool,
+) -> Result<(usize, usize)> {
+    let mut extensions = vec![
+        ExtensionType::ConfidentialTransferMint,
+        ExtensionType::MetadataPointer,
+    ];
+    if confidential_supply {
+        extensions.push(ExtensionType::ConfidentialMintBurn);
+    }
+    if transfer_fee {
+        extensions.push(ExtensionType::TransferFeeConfig);
+        extensions.push(ExtensionType::ConfidentialTransferFeeConfig);
+    }
+
+    // Authority and mint are fixed-width fields, so defaults size the TLV entry correctly
+    let metadata = TokenMetadata {
+        name: name.to_string(),
+        symbol: symbol.to_string(),
+        uri: uri.to_string(),
+        ..Default::default()
+    };
+    let space = ExtensionType::try_calculate_account_len::<Mint>(&extensions)?;
+    Ok((space, metadata.tlv_size_of()?))
 }
 
-async fn create_and_configure_ata(
-    rpc_url: &str,
+fn create_ata(
+    rpc: &RpcClient,
     owner: &Keypair,
     mint: &Pubkey,
-    owner_keys: &ConfidentialKeys,
+    receipt: &mut LaunchReceipt,
 ) -> Result<()> {
-    let rpc = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
-
-    let ata = get_associated_token_address_with_program_id(
-        &owner.pubkey(),
-        mint,
-        &spl_token_2022::id(),
-    );
-
-    // Create the ATA
     let create_ata_ix = create_associated_token_account(
         &owner.pubkey(),
         &owner.pubkey(),
@@ -167,60 +720,54 @@ async fn create_and_configure_ata(
         &spl_token_2022::id(),
     );
 
-    let recent_blockhash = rpc.get_latest_blockhash()?;
-    let tx = Transaction::new_signed_with_payer(
+    let tx = compute_budget::signed_transaction(
+        rpc,
         &[create_ata_ix],
-        Some(&owner.pubkey()),
+        &owner.pubkey(),
         &[owner],
-        recent_blockhash,
-    );
+    )?;
 
-    rpc.send_and_confirm_transaction_with_spinner(&tx)
+    let signature = rpc.send_and_confirm_transaction_with_spinner(&tx)
         .context("Failed to create token account")?;
+    receipt.record("create_token_account", signature)?;
+
+    Ok(())
+}
 
-    // Reallocate for confidential transfer extension
+async fn configure_ata(
+    rpc: &RpcClient,
+    token: &Token<ProgramRpcClientSendTransaction>,
+    owner: &Keypair,
+    ata: &Pubkey,
+    owner_keys: &ConfidentialKeys,
+    receipt: &mut LaunchReceipt,
+) -> Result<()> {
+    // Reallocate for confidential transfer extension (no-op if the space is already there)
+    let account_data = rpc.get_account(ata)?;
     let reallocate_ix = reallocate(
         &spl_token_2022::id(),
-        &ata,
+        ata,
         &owner.pubkey(),
         &owner.pubkey(),
         &[&owner.pubkey()],
-        &[ExtensionType::ConfidentialTransferAccount],
+        &confidential_account_extensions(&account_data.data)?,
     )?;
 
-    let recent_blockhash = rpc.get_latest_blockhash()?;
-    let tx = Transaction::new_signed_with_payer(
+    let tx = compute_budget::signed_transaction(
+        rpc,
         &[reallocate_ix],
-        Some(&owner.pubkey()),
+        &owner.pubkey(),
         &[owner],
-        recent_blockhash,
-    );
+    )?;
 
-    rpc.send_and_confirm_transaction_with_spinner(&tx)
+    let signature = rpc.send_and_confirm_transaction_with_spinner(&tx)
         .context("Failed to reallocate token account for confidential transfer")?;
+    receipt.record("reallocate_token_account", signature)?;
 
     // Use Token client to configure the account - it handles proof generation
-    let rpc_client = Arc::new(NonblockingRpcClient::new_with_commitment(
-        rpc_url.to_string(),
-        CommitmentConfig::confirmed(),
-    ));
-
-    let program_client = Arc::new(ProgramRpcClient::new(
-        rpc_client.clone(),
-        ProgramRpcClientSendTransaction,
-    ));
-
-    let token = Token::new(
-        program_client,
-        &spl_token_2022::id(),
-        mint,
-        None,
-        Arc::new(owner.insecure_clone()),
-    );
-
-    token
+    let response = token
         .confidential_transfer_configure_token_account(
-            &ata,
+            ata,
             &owner.pubkey(),
             None, // context state account
             None, // maximum pending balance credit counter
@@ -230,64 +777,122 @@ async fn create_and_configure_ata(
         )
         .await
         .map_err(|e| anyhow!("Failed to configure confidential transfer account: {}", e))?;
+    receipt.record_response("configure_token_account", response)?;
 
     Ok(())
 }
 
-async fn mint_and_deposit(
-    rpc_url: &str,
+/// Mints straight into the authority's pending confidential balance, so the supply is
+/// only ever recorded encrypted under the supply ElGamal key.
+#[allow(clippy::too_many_arguments)]
+async fn confidential_mint(
+    rpc: &RpcClient,
+    token: &Token<ProgramRpcClientSendTransaction>,
     authority: &Keypair,
     mint: &Pubkey,
-    destination: &Pubkey,
+    ata: &Pubkey,
     authority_keys: &ConfidentialKeys,
     amount: u64,
-    decimals: u8,
+    receipt: &mut LaunchReceipt,
 ) -> Result<()> {
-    let rpc_client = Arc::new(NonblockingRpcClient::new_with_commitment(
-        rpc_url.to_string(),
-        CommitmentConfig::confirmed(),
-    ));
-
-    let program_client = Arc::new(ProgramRpcClient::new(
-        rpc_client.clone(),
-        ProgramRpcClientSendTransaction,
-    ));
+    let mint_account_data = rpc.get_account(mint)?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_account_data.data)?;
+    let supply_info = SupplyAccountInfo::new(mint_state.get_extension::<ConfidentialMintBurn>()?);
+    let ct_mint = mint_state.get_extension::<ConfidentialTransferMint>()?;
+    let auditor_elgamal_pubkey: Option<ElGamalPubkey> =
+        Option::<PodElGamalPubkey>::from(ct_mint.auditor_elgamal_pubkey)
+            .and_then(|p| ElGamalPubkey::try_from(p).ok());
+
+    let account_data = rpc.get_account(ata)?;
+    let account_state =
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    let ct_account = account_state.get_extension::<ConfidentialTransferAccount>()?;
+    let dest_elgamal_pubkey: ElGamalPubkey = ct_account.elgamal_pubkey.try_into()
+        .map_err(|_| anyhow!("Invalid destination ElGamal pubkey"))?;
+
+    let proof_data = supply_info
+        .generate_split_mint_proof_data(
+            amount,
+            &authority_keys.elgamal_keypair,
+            &authority_keys.aes_key,
+            &dest_elgamal_pubkey,
+            auditor_elgamal_pubkey.as_ref(),
+        )
+        .map_err(|e| anyhow!("Failed to generate mint proof data: {}", e))?;
+    let new_decryptable_supply = supply_info
+        .new_decryptable_supply(amount, &authority_keys.elgamal_keypair, &authority_keys.aes_key)
+        .map_err(|e| anyhow!("Failed to compute new decryptable supply: {}", e))?;
+
+    let equality_proof_keypair = Keypair::new();
+    let ciphertext_validity_proof_keypair = Keypair::new();
+    let range_proof_keypair = Keypair::new();
+
+    token.confidential_transfer_create_context_state_account(
+        &equality_proof_keypair.pubkey(),
+        &authority.pubkey(),
+        &proof_data.equality_proof_data,
+        false,
+        &[authority, &equality_proof_keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to create equality proof account: {}", e))?;
+
+    token.confidential_transfer_create_context_state_account(
+        &ciphertext_validity_proof_keypair.pubkey(),
+        &authority.pubkey(),
+        &proof_data.ciphertext_validity_proof_data_with_ciphertext.proof_data,
+        true,
+        &[authority, &ciphertext_validity_proof_keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to create ciphertext validity proof account: {}", e))?;
+
+    token.confidential_transfer_create_context_state_account(
+        &range_proof_keypair.pubkey(),
+        &authority.pubkey(),
+        &proof_data.range_proof_data,
+        true,
+        &[authority, &range_proof_keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to create range proof account: {}", e))?;
 
-    let token = Token::new(
-        program_client,
+    let mint_ixs = confidential_mint_with_split_proofs(
         &spl_token_2022::id(),
+        ata,
         mint,
-        Some(decimals),
-        Arc::new(authority.insecure_clone()),
-    );
-
-    token
-        .mint_to(destination, &authority.pubkey(), amount, &[authority])
-        .await
-        .map_err(|e| anyhow!("Failed to mint tokens: {}", e))?;
+        &proof_data.ciphertext_validity_proof_data_with_ciphertext.ciphertext_lo,
+        &proof_data.ciphertext_validity_proof_data_with_ciphertext.ciphertext_hi,
+        &authority.pubkey(),
+        &[],
+        ProofLocation::ContextStateAccount(&equality_proof_keypair.pubkey()),
+        ProofLocation::ContextStateAccount(&ciphertext_validity_proof_keypair.pubkey()),
+        ProofLocation::ContextStateAccount(&range_proof_keypair.pubkey()),
+        &new_decryptable_supply.into(),
+    )?;
 
-    token
-        .confidential_transfer_deposit(
-            destination,
-            &authority.pubkey(),
-            amount,
-            decimals,
-            &[authority],
-        )
-        .await
-        .map_err(|e| anyhow!("Failed to deposit to confidential balance: {}", e))?;
+    let tx = compute_budget::signed_transaction(
+        rpc,
+        &mint_ixs,
+        &authority.pubkey(),
+        &[authority],
+    )?;
 
-    token
-        .confidential_transfer_apply_pending_balance(
-            destination,
+    let signature = rpc.send_and_confirm_transaction_with_spinner(&tx)
+        .context("Failed to mint confidential supply")?;
+    receipt.record("confidential_mint", signature)?;
+
+    // Close context state accounts to recover rent
+    for proof_account in [
+        &equality_proof_keypair,
+        &ciphertext_validity_proof_keypair,
+        &range_proof_keypair,
+    ] {
+        token.confidential_transfer_close_context_state_account(
+            &proof_account.pubkey(),
+            ata,
             &authority.pubkey(),
-            None,
-            authority_keys.elgamal_keypair.secret(),
-            &authority_keys.aes_key,
             &[authority],
-        )
-        .await
-        .map_err(|e| anyhow!("Failed to apply pending balance: {}", e))?;
+        ).await
+        .map_err(|e| anyhow!("Failed to close proof account: {}", e))?;
+    }
 
     Ok(())
 }
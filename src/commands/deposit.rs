@@ -0,0 +1,132 @@
+use crate::cli::DepositArgs;
+use crate::commands::balance::{decode_balances, PendingBalance};
+use crate::compute_budget;
+use crate::config::{expand_path, load_keypair};
+use crate::crypto::ConfidentialKeys;
+use anyhow::{anyhow, Context, Result};
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::signature::Signer;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::extension::{
+    confidential_mint_burn::ConfidentialMintBurn,
+    confidential_transfer::ConfidentialTransferAccount,
+    BaseStateWithExtensions, StateWithExtensions,
+};
+use spl_token_client::{
+    client::{ProgramRpcClient, ProgramRpcClientSendTransaction},
+    token::Token,
+};
+use std::sync::Arc;
+
+pub async fn execute(args: DepositArgs) -> Result<()> {
+    let keypair = load_keypair(&expand_path(&args.keypair))?;
+    let owner_keys = ConfidentialKeys::derive_from_keypair(&keypair)?;
+
+    let rpc_client = Arc::new(NonblockingRpcClient::new_with_commitment(
+        args.rpc.clone(),
+        CommitmentConfig::confirmed(),
+    ));
+
+    let program_client = Arc::new(ProgramRpcClient::new(
+        rpc_client.clone(),
+        ProgramRpcClientSendTransaction,
+    ));
+
+    let ata = get_associated_token_address_with_program_id(
+        &keypair.pubkey(),
+        &args.mint,
+        &spl_token_2022::id(),
+    );
+
+    status!("Depositing to confidential balance...");
+    status!("  Mint: {}", args.mint);
+    status!("  Token Account: {}", ata);
+    status!("  Amount: {}", args.amount);
+
+    let mint_account_data = rpc_client.get_account(&args.mint).await?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_account_data.data)?;
+    if mint_state.get_extension::<ConfidentialMintBurn>().is_ok() {
+        return Err(anyhow!(
+            "Deposit is disabled on mint {} - its supply is managed by confidential mint/burn",
+            args.mint
+        ));
+    }
+    let decimals = mint_state.base.decimals;
+
+    let account_data = rpc_client
+        .get_account(&ata)
+        .await
+        .with_context(|| format!("Token account not found for wallet {}", keypair.pubkey()))?;
+    let account_state =
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)
+            .context("Failed to unpack token account")?;
+    account_state
+        .get_extension::<ConfidentialTransferAccount>()
+        .context("Confidential transfer extension not found on account - run configure first")?;
+
+    let public_balance = account_state.base.amount;
+    if args.amount > public_balance {
+        return Err(anyhow!(
+            "Insufficient public balance. Need {}, have {}",
+            args.amount,
+            public_balance
+        ));
+    }
+
+    let token = compute_budget::budgeted_token(
+        Token::new(
+            program_client,
+            &spl_token_2022::id(),
+            &args.mint,
+            Some(decimals),
+            Arc::new(keypair.insecure_clone()),
+        ),
+        &rpc_client,
+        &[ata],
+    )
+    .await?;
+
+    token
+        .confidential_transfer_deposit(
+            &ata,
+            &keypair.pubkey(),
+            args.amount,
+            decimals,
+            &[&keypair],
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to deposit to confidential balance: {}", e))?;
+    status!("  Deposited to pending balance");
+
+    if args.apply {
+        token
+            .confidential_transfer_apply_pending_balance(
+                &ata,
+                &keypair.pubkey(),
+                None,
+                owner_keys.elgamal_keypair.secret(),
+                &owner_keys.aes_key,
+                &[&keypair],
+            )
+            .await
+            .map_err(|e| anyhow!("Failed to apply pending balance: {}", e))?;
+        status!("  Applied pending balance");
+    }
+
+    let account_data = rpc_client.get_account(&ata).await?;
+    let balances = decode_balances(&account_data.data, &owner_keys)?;
+
+    status!("\nDeposit complete.");
+    status!("Public balance: {}", balances.public);
+    status!("Confidential balance: {}", balances.available);
+    match balances.pending {
+        PendingBalance::Decrypted(0) => {}
+        PendingBalance::Decrypted(pending) => {
+            status!("Pending balance: {} (use apply-pending to make it available)", pending);
+        }
+        PendingBalance::Undecryptable => status!("Pending balance: could not decrypt"),
+    }
+
+    Ok(())
+}
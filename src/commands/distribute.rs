@@ -1,39 +1,75 @@
-use crate::cli::DistributeArgs;
-use crate::config::{expand_path, load_keypair};
+use crate::cli::{DistributeArgs, UnreadyPolicy};
+use crate::commands::balance::verify_available_balance;
+use crate::compute_budget;
+use crate::config::{create_rpc_client, expand_path, load_keypair};
 use crate::crypto::ConfidentialKeys;
+use crate::dry_run::DryRun;
+use crate::journal::{DistributionJournal, JournalEntry, TransferStatus};
+use crate::output;
 use anyhow::{anyhow, Context, Result};
-use csv::Reader;
-use serde::Deserialize;
+use csv::{Reader, Writer};
+use serde::{Deserialize, Serialize};
 use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
 use solana_commitment_config::CommitmentConfig;
 use solana_sdk::{
+    instruction::Instruction,
     pubkey::Pubkey,
     signature::{Keypair, Signer},
 };
-use spl_token_2022::solana_zk_sdk::encryption::{
-    auth_encryption::AeCiphertext,
-    elgamal::ElGamalPubkey,
+use spl_token_2022::solana_zk_sdk::{
+    encryption::{
+        auth_encryption::AeCiphertext,
+        elgamal::{ElGamalCiphertext, ElGamalPubkey},
+        pedersen::PedersenOpening,
+    },
+    zk_elgamal_proof_program::{instruction::ProofInstruction, proof_data::ZkProofData},
 };
 use spl_associated_token_account::get_associated_token_address_with_program_id;
 use spl_token_2022::extension::{
     confidential_transfer::{ConfidentialTransferAccount, ConfidentialTransferMint},
+    confidential_transfer_fee::ConfidentialTransferFeeConfig,
+    transfer_fee::TransferFeeConfig,
     BaseStateWithExtensions, StateWithExtensions,
 };
 use spl_token_client::{
-    client::{ProgramRpcClient, ProgramRpcClientSendTransaction},
+    client::{ProgramRpcClient, ProgramRpcClientSendTransaction, RpcClientResponse},
     token::{ProofAccountWithCiphertext, Token},
 };
-use spl_token_confidential_transfer_proof_generation::transfer::transfer_split_proof_data;
+use spl_token_confidential_transfer_proof_generation::{
+    transfer::transfer_split_proof_data,
+    transfer_with_fee::transfer_with_fee_split_proof_data,
+};
+use std::fs;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Deserialize)]
-struct Recipient {
-    wallet: String,
+pub struct Recipient {
+    pub wallet: String,
+    pub amount: u64,
+}
+
+/// Result document for `--output json`.
+#[derive(Serialize)]
+struct DistributionReport<'a> {
+    mint: &'a str,
+    authority: &'a str,
+    journal: Option<String>,
+    readiness: Vec<ReadinessRow<'a>>,
+    recipients: &'a [JournalEntry],
+    remaining_balance: Option<u64>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ReadinessRow<'a> {
+    wallet: &'a str,
     amount: u64,
+    readiness: &'static str,
 }
 
-pub async fn execute(args: DistributeArgs) -> Result<()> {
+pub async fn execute(args: DistributeArgs, dry_run: bool) -> Result<()> {
     let keypair = load_keypair(&expand_path(&args.keypair))?;
 
     let rpc_client = Arc::new(NonblockingRpcClient::new_with_commitment(
@@ -46,8 +82,75 @@ pub async fn execute(args: DistributeArgs) -> Result<()> {
         ProgramRpcClientSendTransaction,
     ));
 
-    let recipients = parse_recipients(&expand_path(&args.recipients))?;
-    println!("Distributing tokens to {} recipients...", recipients.len());
+    // --retry-pending reads a queue file written by an earlier parked run; it has the same
+    // wallet,amount columns as a recipients CSV
+    let recipients_source = args.recipients.as_ref().or(args.retry_pending.as_ref());
+    let park_unready = args.unready == UnreadyPolicy::Park || args.retry_pending.is_some();
+    let queue_path = match (&args.retry_pending, &args.pending_queue) {
+        (Some(path), _) | (None, Some(path)) => expand_path(path),
+        (None, None) => {
+            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+            format!("pending-{}-{}.csv", args.mint, timestamp).into()
+        }
+    };
+
+    let mut journal = match (&args.resume, recipients_source) {
+        (Some(path), _) => {
+            let journal = DistributionJournal::load(&expand_path(path))?;
+            if journal.mint != args.mint.to_string() {
+                return Err(anyhow!(
+                    "Journal is for mint {}, not {}",
+                    journal.mint,
+                    args.mint
+                ));
+            }
+            if journal.authority != keypair.pubkey().to_string() {
+                return Err(anyhow!(
+                    "Journal was written by authority {}, not {}",
+                    journal.authority,
+                    keypair.pubkey()
+                ));
+            }
+            status!("Resuming distribution from {}", path.display());
+            journal
+        }
+        (None, Some(recipients_path)) => {
+            let recipients = parse_recipients(&expand_path(recipients_path))?;
+            if args.retry_pending.is_some() {
+                status!("Retrying {} pending recipients from {}", recipients.len(), recipients_path.display());
+            }
+            // A readiness check or dry run sends nothing, so there is nothing to journal
+            let path = match &args.journal {
+                _ if args.check || dry_run => None,
+                Some(path) => Some(expand_path(path)),
+                None => {
+                    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+                    Some(format!("distribute-{}-{}.json", args.mint, timestamp).into())
+                }
+            };
+            let journal = DistributionJournal {
+                mint: args.mint.to_string(),
+                authority: keypair.pubkey().to_string(),
+                entries: recipients
+                    .into_iter()
+                    .map(|r| JournalEntry::new(r.wallet, r.amount))
+                    .collect(),
+                path,
+            };
+            journal.save()?;
+            journal
+        }
+        (None, None) => return Err(anyhow!("Either --recipients or --resume is required")),
+    };
+    if dry_run {
+        // Leave a resumed journal untouched
+        journal.path = None;
+    }
+
+    let journal_path = journal.path.clone().unwrap_or_default();
+    if journal.path.is_some() {
+        status!("Journal: {}", journal_path.display());
+    }
 
     let authority_keys = ConfidentialKeys::derive_from_keypair(&keypair)?;
 
@@ -57,203 +160,1192 @@ pub async fn execute(args: DistributeArgs) -> Result<()> {
         &spl_token_2022::id(),
     );
 
-    // Get current balance
-    let source_account_data = rpc_client.get_account(&source_ata).await?;
+    // Get decimals and auditor pubkey from the mint
+    let (decimals, auditor_elgamal_pubkey, transfer_fee) =
+        fetch_mint_transfer_config(&rpc_client, &args.mint).await?;
+    if let Some(fee) = &transfer_fee {
+        status!("Transfer fee: {} bps (max {})", fee.basis_points, fee.maximum_fee);
+    }
+
+    let token = compute_budget::budgeted_token(
+        Token::new(
+            program_client.clone(),
+            &spl_token_2022::id(),
+            &args.mint,
+            Some(decimals),
+            Arc::new(keypair.insecure_clone()),
+        ),
+        &rpc_client,
+        &[source_ata],
+    )
+    .await?;
+
+    let source = TransferSource {
+        rpc_client: &rpc_client,
+        token: &token,
+        authority: &keypair,
+        authority_keys: &authority_keys,
+        source_ata,
+        mint: args.mint,
+        auditor_elgamal_pubkey,
+        transfer_fee,
+    };
+
+    // Settle anything an earlier run left half-done before computing what is still owed
+    if !args.check && !dry_run {
+        for i in 0..journal.entries.len() {
+            recover_entry(&source, &mut journal, i).await?;
+        }
+    }
+
+    // Pre-flight: classify every outstanding recipient before any transaction is sent
+    let outstanding: Vec<(String, u64)> = journal
+        .entries
+        .iter()
+        .filter(|e| !matches!(e.status, TransferStatus::Completed | TransferStatus::Parked))
+        .map(|e| (e.wallet.clone(), e.amount))
+        .collect();
+    let wallets: Vec<String> = outstanding.iter().map(|(w, _)| w.clone()).collect();
+    let readiness = check_readiness(&rpc_client, &args.mint, &wallets).await?;
+    let report: Vec<(String, u64, Readiness)> = outstanding
+        .into_iter()
+        .zip(readiness)
+        .map(|((wallet, amount), r)| (wallet, amount, r))
+        .collect();
+
+    status!("Recipient readiness:");
+    for (wallet, amount, r) in &report {
+        status!("  {:<44} {:>20}  {}", wallet, amount, r.label());
+    }
+
+    let not_ready = report.iter().filter(|(_, _, r)| *r != Readiness::Ready).count();
+    status!("  {} ready, {} not ready", report.len() - not_ready, not_ready);
+
+    if let Some(path) = &args.report {
+        write_readiness_report(&expand_path(path), &report)?;
+        status!("Readiness report: {}", path.display());
+    }
+
+    if args.check {
+        emit_report(&journal, &report, None, None)?;
+        return Ok(());
+    }
+
+    let invalid = report.iter().filter(|(_, _, r)| *r == Readiness::InvalidPubkey).count();
+    if invalid > 0 {
+        let error =
+            anyhow!("{} recipients have invalid wallet addresses - fix the CSV", invalid);
+        emit_report(&journal, &report, None, Some(&error))?;
+        return Err(error);
+    }
+
+    if not_ready > 0 && !park_unready {
+        let error = anyhow!(
+            "{} recipients are not ready for confidential transfers - fix or remove them (see --check), or use --unready park",
+            not_ready
+        );
+        emit_report(&journal, &report, None, Some(&error))?;
+        return Err(error);
+    }
+
+    if park_unready && (not_ready > 0 || args.retry_pending.is_some()) {
+        let parked: Vec<(String, u64, Readiness)> = report
+            .iter()
+            .filter(|(_, _, r)| *r != Readiness::Ready)
+            .cloned()
+            .collect();
+
+        for entry in journal.entries.iter_mut() {
+            if entry.status != TransferStatus::Completed
+                && parked.iter().any(|(wallet, _, _)| *wallet == entry.wallet)
+            {
+                entry.status = TransferStatus::Parked;
+            }
+        }
+        journal.save()?;
+
+        if dry_run {
+            status!("Would park {} unready recipients in {}", parked.len(), queue_path.display());
+        } else {
+            // A retry rewrites its own queue; otherwise earlier parked wallets are kept
+            let queued = if args.retry_pending.is_some() {
+                parked.clone()
+            } else {
+                merge_parked(&queue_path, &parked)?
+            };
+            write_readiness_report(&queue_path, &queued)?;
+            if queued.is_empty() {
+                status!("All pending recipients are now ready");
+            } else {
+                let instructions_path = queue_path.with_extension("md");
+                write_onboarding_instructions(&instructions_path, &args.mint, &args.rpc, &queued)?;
+                status!("Parked {} unready recipients in {}", parked.len(), queue_path.display());
+                if queued.len() > parked.len() {
+                    status!("  {} parked by earlier runs are still queued", queued.len() - parked.len());
+                }
+                status!("Onboarding instructions: {}", instructions_path.display());
+                status!(
+                    "Once they have configured, run distribute --retry-pending {}",
+                    queue_path.display()
+                );
+            }
+        }
+    }
+
+    if dry_run {
+        return dry_run_distribution(&source, &journal, &args.rpc).await;
+    }
+
+    status!("Distributing tokens to {} recipients...", journal.entries.len());
+
+    let mut current_balance = fetch_source_balance(&source).await?;
+    status!("Current confidential balance: {}", current_balance);
+
+    let total = journal.entries.len();
+    for i in 0..total {
+        let (wallet, amount, status) = {
+            let entry = &journal.entries[i];
+            (entry.wallet.clone(), entry.amount, entry.status)
+        };
+
+        if status == TransferStatus::Completed {
+            status!("[{}/{}] {} to {} already completed (skipping)", i + 1, total, amount, wallet);
+            continue;
+        }
+        if status == TransferStatus::Parked {
+            status!("[{}/{}] {} to {} parked until recipient is ready", i + 1, total, amount, wallet);
+            continue;
+        }
+
+        let wallet = Pubkey::from_str(&wallet)
+            .with_context(|| format!("Invalid wallet address: {}", wallet))?;
+
+        if amount > current_balance {
+            return Err(anyhow!(
+                "Insufficient balance for recipient {}. Need {}, have {}",
+                wallet,
+                amount,
+                current_balance
+            ));
+        }
+
+        status!("[{}/{}] Transferring {} to {}", i + 1, total, amount, wallet);
+
+        if let Err(e) = transfer_to_wallet(&source, &mut journal, i, None).await {
+            journal.entries[i].error = Some(e.to_string());
+            journal.save()?;
+            let error = e.context(format!(
+                "Distribution interrupted - re-run with --resume {}",
+                journal_path.display()
+            ));
+            emit_report(&journal, &report, Some(current_balance), Some(&error))?;
+            return Err(error);
+        }
+
+        current_balance -= amount;
+        status!("  Transfer complete");
+    }
+
+    status!("\nDistribution complete.");
+    status!("Remaining balance: {}", current_balance);
+
+    emit_report(&journal, &report, Some(current_balance), None)?;
+    Ok(())
+}
+
+/// Emits the per-recipient results of this run as the `--output json` document.
+fn emit_report(
+    journal: &DistributionJournal,
+    readiness: &[(String, u64, Readiness)],
+    remaining_balance: Option<u64>,
+    error: Option<&anyhow::Error>,
+) -> Result<()> {
+    output::emit(&DistributionReport {
+        mint: &journal.mint,
+        authority: &journal.authority,
+        journal: journal.path.as_ref().map(|p| p.display().to_string()),
+        readiness: readiness
+            .iter()
+            .map(|(wallet, amount, r)| ReadinessRow {
+                wallet,
+                amount: *amount,
+                readiness: r.label(),
+            })
+            .collect(),
+        recipients: &journal.entries,
+        remaining_balance,
+        error: error.map(|e| format!("{:#}", e)),
+    })
+}
+
+/// `--dry-run`: generates and locally verifies the proofs of every outstanding transfer,
+/// debiting each amount from a local copy of the source balance, and simulates the
+/// on-chain verification of the first transfer's proofs. Nothing is sent.
+async fn dry_run_distribution(
+    source: &TransferSource<'_>,
+    journal: &DistributionJournal,
+    rpc_url: &str,
+) -> Result<()> {
+    let TransferSource {
+        rpc_client,
+        authority,
+        authority_keys,
+        source_ata,
+        ..
+    } = source;
+    let rpc = create_rpc_client(rpc_url);
+    let mut simulation = DryRun::default();
+
+    let mut balance = fetch_source_balance(source).await?;
+    status!("Current confidential balance: {}", balance);
+
+    let source_account_data = rpc_client.get_account(source_ata).await?;
+    let source_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&source_account_data.data)?;
+    let source_ct = source_state.get_extension::<ConfidentialTransferAccount>()?;
+    let mut available_balance: ElGamalCiphertext = source_ct.available_balance.try_into()
+        .map_err(|_| anyhow!("Invalid source available balance"))?;
+
+    let mut transfers = 0;
+    for entry in &journal.entries {
+        match entry.status {
+            TransferStatus::Completed | TransferStatus::Parked => continue,
+            TransferStatus::Pending => {}
+            TransferStatus::ProofAccountsCreated | TransferStatus::Transferred => {
+                simulation.skip(
+                    &format!("transfer {} to {}", entry.amount, entry.wallet),
+                    "interrupted transfer, which a real run recovers first",
+                );
+                continue;
+            }
+        }
+
+        let step = format!("proofs for {} to {}", entry.amount, entry.wallet);
+        let proofs = if entry.amount > balance {
+            Err(anyhow!("Insufficient balance: need {}, have {}", entry.amount, balance))
+        } else {
+            // Proofs only read the balance through its decryptable ciphertext
+            let decryptable = authority_keys.aes_key.encrypt(balance);
+            verify_proof_instructions(
+                source,
+                &entry.wallet,
+                entry.amount,
+                &available_balance,
+                &decryptable,
+            ).await
+        };
+
+        let verify_instructions = match proofs {
+            Ok(verify_instructions) => verify_instructions,
+            Err(e) => {
+                simulation.check(&step, Err(e));
+                continue;
+            }
+        };
+        simulation.check(&step, Ok(()));
+
+        // Every transfer verifies the same kinds of proof, so simulating one set is enough
+        if transfers == 0 {
+            for (name, instruction) in verify_instructions {
+                simulation.simulate(&rpc, name, &[instruction], &authority.pubkey(), &[*authority])?;
+            }
+        }
+
+        transfers += 1;
+        balance -= entry.amount;
+        available_balance = &available_balance
+            - &authority_keys
+                .elgamal_keypair
+                .pubkey()
+                .encrypt_with(entry.amount, &PedersenOpening::default());
+    }
+
+    if transfers > 0 {
+        simulation.skip(
+            "transfers",
+            &format!(
+                "{} transfers need their proof context accounts created first (rent is refunded when they are closed)",
+                transfers
+            ),
+        );
+    }
+    status!("Balance after distribution: {}", balance);
+
+    simulation.finish()
+}
+
+/// Generates the split proofs of a transfer, verifies them locally, and returns the
+/// instructions that verify them on chain without a context account.
+async fn verify_proof_instructions(
+    source: &TransferSource<'_>,
+    wallet: &str,
+    amount: u64,
+    source_available_balance: &ElGamalCiphertext,
+    source_decryptable: &AeCiphertext,
+) -> Result<Vec<(&'static str, Instruction)>> {
+    let TransferSource {
+        rpc_client,
+        authority_keys,
+        mint,
+        auditor_elgamal_pubkey,
+        transfer_fee,
+        ..
+    } = source;
+
+    let wallet = Pubkey::from_str(wallet)
+        .with_context(|| format!("Invalid wallet address: {}", wallet))?;
+    let dest_ata = get_associated_token_address_with_program_id(
+        &wallet,
+        mint,
+        &spl_token_2022::id(),
+    );
+    let dest_account_data = rpc_client.get_account(&dest_ata).await
+        .with_context(|| format!("Token account not found for recipient {}", wallet))?;
+    let dest_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&dest_account_data.data)?;
+    let dest_ct = dest_state.get_extension::<ConfidentialTransferAccount>()?;
+    let dest_elgamal_pubkey: ElGamalPubkey = dest_ct.elgamal_pubkey.try_into()
+        .map_err(|_| anyhow!("Invalid destination ElGamal pubkey"))?;
+
+    match transfer_fee {
+        Some(fee) => {
+            let proof_data = transfer_with_fee_split_proof_data(
+                source_available_balance,
+                source_decryptable,
+                amount,
+                &authority_keys.elgamal_keypair,
+                &authority_keys.aes_key,
+                &dest_elgamal_pubkey,
+                auditor_elgamal_pubkey.as_ref(),
+                &fee.withdraw_withheld_authority_elgamal_pubkey,
+                fee.basis_points,
+                fee.maximum_fee,
+            ).map_err(|e| anyhow!("Failed to generate proof data: {:?}", e))?;
+            let transfer_amount_validity_proof_data =
+                &proof_data.transfer_amount_ciphertext_validity_proof_data_with_ciphertext.proof_data;
+
+            proof_data.equality_proof_data.verify_proof()
+                .map_err(|e| anyhow!("Equality proof does not verify: {:?}", e))?;
+            transfer_amount_validity_proof_data.verify_proof()
+                .map_err(|e| anyhow!("Ciphertext validity proof does not verify: {:?}", e))?;
+            proof_data.percentage_with_cap_proof_data.verify_proof()
+                .map_err(|e| anyhow!("Percentage-with-cap proof does not verify: {:?}", e))?;
+            proof_data.fee_ciphertext_validity_proof_data.verify_proof()
+                .map_err(|e| anyhow!("Fee ciphertext validity proof does not verify: {:?}", e))?;
+            proof_data.range_proof_data.verify_proof()
+                .map_err(|e| anyhow!("Range proof does not verify: {:?}", e))?;
+
+            Ok(vec![
+                (
+                    "verify_equality_proof",
+                    ProofInstruction::VerifyCiphertextCommitmentEquality
+                        .encode_verify_proof(None, &proof_data.equality_proof_data),
+                ),
+                (
+                    "verify_ciphertext_validity_proof",
+                    ProofInstruction::VerifyBatchedGroupedCiphertext3HandlesValidity
+                        .encode_verify_proof(None, transfer_amount_validity_proof_data),
+                ),
+                (
+                    "verify_percentage_with_cap_proof",
+                    ProofInstruction::VerifyPercentageWithCap
+                        .encode_verify_proof(None, &proof_data.percentage_with_cap_proof_data),
+                ),
+                (
+                    "verify_fee_ciphertext_validity_proof",
+                    ProofInstruction::VerifyBatchedGroupedCiphertext2HandlesValidity
+                        .encode_verify_proof(None, &proof_data.fee_ciphertext_validity_proof_data),
+                ),
+                (
+                    "verify_range_proof",
+                    ProofInstruction::VerifyBatchedRangeProofU256
+                        .encode_verify_proof(None, &proof_data.range_proof_data),
+                ),
+            ])
+        }
+        None => {
+            let proof_data = transfer_split_proof_data(
+                source_available_balance,
+                source_decryptable,
+                amount,
+                &authority_keys.elgamal_keypair,
+                &authority_keys.aes_key,
+                &dest_elgamal_pubkey,
+                auditor_elgamal_pubkey.as_ref(),
+            ).map_err(|e| anyhow!("Failed to generate proof data: {:?}", e))?;
+            let validity_proof_data = &proof_data.ciphertext_validity_proof_data_with_ciphertext.proof_data;
+
+            proof_data.equality_proof_data.verify_proof()
+                .map_err(|e| anyhow!("Equality proof does not verify: {:?}", e))?;
+            validity_proof_data.verify_proof()
+                .map_err(|e| anyhow!("Ciphertext validity proof does not verify: {:?}", e))?;
+            proof_data.range_proof_data.verify_proof()
+                .map_err(|e| anyhow!("Range proof does not verify: {:?}", e))?;
+
+            Ok(vec![
+                (
+                    "verify_equality_proof",
+                    ProofInstruction::VerifyCiphertextCommitmentEquality
+                        .encode_verify_proof(None, &proof_data.equality_proof_data),
+                ),
+                (
+                    "verify_ciphertext_validity_proof",
+                    ProofInstruction::VerifyBatchedGroupedCiphertext3HandlesValidity
+                        .encode_verify_proof(None, validity_proof_data),
+                ),
+                (
+                    "verify_range_proof",
+                    ProofInstruction::VerifyBatchedRangeProofU128
+                        .encode_verify_proof(None, &proof_data.range_proof_data),
+                ),
+            ])
+        }
+    }
+}
+
+/// Everything needed to send confidential transfers out of the authority's token account.
+pub struct TransferSource<'a> {
+    pub rpc_client: &'a NonblockingRpcClient,
+    pub token: &'a Token<ProgramRpcClientSendTransaction>,
+    pub authority: &'a Keypair,
+    pub authority_keys: &'a ConfidentialKeys,
+    pub source_ata: Pubkey,
+    pub mint: Pubkey,
+    pub auditor_elgamal_pubkey: Option<ElGamalPubkey>,
+    pub transfer_fee: Option<MintTransferFee>,
+}
+
+/// Current-epoch fee parameters of a mint with a confidential transfer fee.
+pub struct MintTransferFee {
+    pub basis_points: u16,
+    pub maximum_fee: u64,
+    pub withdraw_withheld_authority_elgamal_pubkey: ElGamalPubkey,
+}
+
+/// Reads the decimals, optional auditor ElGamal pubkey and optional transfer fee from a
+/// confidential mint.
+pub async fn fetch_mint_transfer_config(
+    rpc_client: &NonblockingRpcClient,
+    mint: &Pubkey,
+) -> Result<(u8, Option<ElGamalPubkey>, Option<MintTransferFee>)> {
+    let mint_account_data = rpc_client.get_account(mint).await?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_account_data.data)?;
+    let decimals = mint_state.base.decimals;
+    let ct_mint = mint_state.get_extension::<ConfidentialTransferMint>()?;
+    let auditor_elgamal_pubkey: Option<ElGamalPubkey> = Option::<spl_token_2022::solana_zk_sdk::encryption::pod::elgamal::PodElGamalPubkey>::from(ct_mint.auditor_elgamal_pubkey)
+        .and_then(|p| ElGamalPubkey::try_from(p).ok());
+
+    let transfer_fee = match mint_state.get_extension::<TransferFeeConfig>() {
+        Ok(fee_config) => {
+            let ct_fee_config = mint_state.get_extension::<ConfidentialTransferFeeConfig>()
+                .map_err(|_| anyhow!("Mint {} has a transfer fee but no confidential transfer fee config", mint))?;
+            let epoch = rpc_client.get_epoch_info().await?.epoch;
+            let epoch_fee = fee_config.get_epoch_fee(epoch);
+            Some(MintTransferFee {
+                basis_points: u16::from(epoch_fee.transfer_fee_basis_points),
+                maximum_fee: u64::from(epoch_fee.maximum_fee),
+                withdraw_withheld_authority_elgamal_pubkey: ct_fee_config
+                    .withdraw_withheld_authority_elgamal_pubkey
+                    .try_into()
+                    .map_err(|_| anyhow!("Invalid withdraw withheld authority ElGamal pubkey"))?,
+            })
+        }
+        Err(_) => None,
+    };
+
+    Ok((decimals, auditor_elgamal_pubkey, transfer_fee))
+}
+
+/// Decrypts the authority's available confidential balance.
+pub async fn fetch_source_balance(source: &TransferSource<'_>) -> Result<u64> {
+    let source_account_data = source.rpc_client.get_account(&source.source_ata).await?;
     let source_state =
         StateWithExtensions::<spl_token_2022::state::Account>::unpack(&source_account_data.data)?;
     let source_ct = source_state.get_extension::<ConfidentialTransferAccount>()?;
 
     let source_decryptable: AeCiphertext = source_ct.decryptable_available_balance.try_into()
         .map_err(|_| anyhow!("Invalid source decryptable balance"))?;
-    let mut current_balance = authority_keys.aes_key
+    let balance = source.authority_keys.aes_key
         .decrypt(&source_decryptable)
         .ok_or_else(|| anyhow!("Failed to decrypt source balance"))?;
 
-    println!("Current confidential balance: {}", current_balance);
+    // Proofs are generated against the ElGamal balance; a stale decryptable balance would
+    // only surface later as a failed proof
+    if !verify_available_balance(source_ct, &source.authority_keys.elgamal_keypair, balance)? {
+        return Err(anyhow!(
+            "Source decryptable balance {} does not match the encrypted available balance - run `repair-balance --mint {}` first",
+            balance,
+            source.mint
+        ));
+    }
 
-    // Get decimals and auditor pubkey from the mint
-    let mint_account_data = rpc_client.get_account(&args.mint).await?;
-    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_account_data.data)?;
-    let decimals = mint_state.base.decimals;
-    let ct_mint = mint_state.get_extension::<ConfidentialTransferMint>()?;
-    let auditor_elgamal_pubkey: Option<ElGamalPubkey> = Option::<spl_token_2022::solana_zk_sdk::encryption::pod::elgamal::PodElGamalPubkey>::from(ct_mint.auditor_elgamal_pubkey)
-        .and_then(|p| ElGamalPubkey::try_from(p).ok());
+    Ok(balance)
+}
+
+/// Sends a single confidential transfer for a journal entry: generates the split proofs,
+/// verifies them into context state accounts, transfers, and closes the context accounts
+/// again. The journal is saved after each step.
+pub async fn transfer_to_wallet(
+    source: &TransferSource<'_>,
+    journal: &mut DistributionJournal,
+    index: usize,
+    memo: Option<&str>,
+) -> Result<()> {
+    let TransferSource {
+        rpc_client,
+        authority_keys,
+        source_ata,
+        mint,
+        transfer_fee,
+        ..
+    } = source;
 
-    let token = Token::new(
-        program_client.clone(),
+    let wallet = Pubkey::from_str(&journal.entries[index].wallet)
+        .with_context(|| format!("Invalid wallet address: {}", journal.entries[index].wallet))?;
+
+    let dest_ata = get_associated_token_address_with_program_id(
+        &wallet,
+        mint,
         &spl_token_2022::id(),
-        &args.mint,
-        Some(decimals),
-        Arc::new(keypair.insecure_clone()),
     );
 
-    for (i, recipient) in recipients.iter().enumerate() {
-        let wallet = Pubkey::from_str(&recipient.wallet)
-            .with_context(|| format!("Invalid wallet address: {}", recipient.wallet))?;
+    // Get destination's ElGamal pubkey (readiness is checked before any transfer starts)
+    let dest_account_data = rpc_client.get_account(&dest_ata).await
+        .with_context(|| format!("Token account not found for recipient {}", wallet))?;
+    let dest_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&dest_account_data.data)?;
+    let dest_ct = dest_state.get_extension::<ConfidentialTransferAccount>()?;
+    let dest_elgamal_pubkey: ElGamalPubkey = dest_ct.elgamal_pubkey.try_into()
+        .map_err(|_| anyhow!("Invalid destination ElGamal pubkey"))?;
 
-        if recipient.amount > current_balance {
-            return Err(anyhow!(
-                "Insufficient balance for recipient {}. Need {}, have {}",
-                wallet,
-                recipient.amount,
-                current_balance
-            ));
+    // Get fresh source account state for proof generation
+    let source_account_data = rpc_client.get_account(source_ata).await?;
+    let source_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&source_account_data.data)?;
+    let source_ct = source_state.get_extension::<ConfidentialTransferAccount>()?;
+
+    let source_available_balance: ElGamalCiphertext = source_ct.available_balance.try_into()
+        .map_err(|_| anyhow!("Invalid source available balance"))?;
+    let source_decryptable: AeCiphertext = source_ct.decryptable_available_balance.try_into()
+        .map_err(|_| anyhow!("Invalid source decryptable balance"))?;
+
+    journal.entries[index].source_balance_before = Some(
+        authority_keys.aes_key
+            .decrypt(&source_decryptable)
+            .ok_or_else(|| anyhow!("Failed to decrypt source balance"))?,
+    );
+    journal.save()?;
+
+    // Fee-bearing mints need the transfer-with-fee proofs (two extra context accounts)
+    let response = match transfer_fee {
+        Some(fee) => {
+            send_transfer_with_fee(
+                source,
+                journal,
+                index,
+                fee,
+                &dest_ata,
+                &dest_elgamal_pubkey,
+                &source_available_balance,
+                &source_decryptable,
+                memo,
+            ).await?
+        }
+        None => {
+            send_transfer(
+                source,
+                journal,
+                index,
+                &dest_ata,
+                &dest_elgamal_pubkey,
+                &source_available_balance,
+                &source_decryptable,
+                memo,
+            ).await?
         }
+    };
+    journal.record_response(index, "transfer", response)?;
+    journal.set_status(index, TransferStatus::Transferred)?;
 
-        println!(
-            "[{}/{}] Transferring {} to {}",
-            i + 1,
-            recipients.len(),
-            recipient.amount,
-            wallet
-        );
+    // Close context state accounts to recover rent
+    status!("  Cleaning up proof accounts...");
+    close_proof_accounts(source, journal, index).await?;
+    journal.set_status(index, TransferStatus::Completed)?;
 
-        let dest_ata = get_associated_token_address_with_program_id(
-            &wallet,
-            &args.mint,
-            &spl_token_2022::id(),
-        );
+    Ok(())
+}
 
-        // Create destination ATA if needed
-        if rpc_client.get_account(&dest_ata).await.is_err() {
-            token.create_associated_token_account(&wallet).await
-                .map_err(|e| anyhow!("Failed to create recipient token account: {}", e))?;
-            println!("  Created token account for recipient");
-        }
-
-        // Get destination's ElGamal pubkey
-        let dest_account_data = rpc_client.get_account(&dest_ata).await?;
-        let dest_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&dest_account_data.data)?;
-        let dest_ct = dest_state.get_extension::<ConfidentialTransferAccount>()?;
-        let dest_elgamal_pubkey: ElGamalPubkey = dest_ct.elgamal_pubkey.try_into()
-            .map_err(|_| anyhow!("Invalid destination ElGamal pubkey"))?;
-
-        // Get fresh source account state for proof generation
-        let source_account_data = rpc_client.get_account(&source_ata).await?;
-        let source_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&source_account_data.data)?;
-        let source_ct = source_state.get_extension::<ConfidentialTransferAccount>()?;
-
-        let source_available_balance = source_ct.available_balance.try_into()
-            .map_err(|_| anyhow!("Invalid source available balance"))?;
-        let source_decryptable: AeCiphertext = source_ct.decryptable_available_balance.try_into()
-            .map_err(|_| anyhow!("Invalid source decryptable balance"))?;
-
-        // Generate split proof data
-        println!("  Generating proofs...");
-        let proof_data = transfer_split_proof_data(
-            &source_available_balance,
-            &source_decryptable,
-            recipient.amount,
-            &authority_keys.elgamal_keypair,
-            &authority_keys.aes_key,
-            &dest_elgamal_pubkey,
-            auditor_elgamal_pubkey.as_ref(),
-        ).map_err(|e| anyhow!("Failed to generate proof data: {:?}", e))?;
-
-        // Create context state accounts for proofs (split mode for large proofs)
-        let equality_proof_keypair = Keypair::new();
-        let ciphertext_validity_proof_keypair = Keypair::new();
-        let range_proof_keypair = Keypair::new();
-
-        println!("  Creating proof context accounts...");
-
-        // Create equality proof context state (small enough for single tx)
-        token.confidential_transfer_create_context_state_account(
-            &equality_proof_keypair.pubkey(),
-            &keypair.pubkey(),
-            &proof_data.equality_proof_data,
-            false,
-            &[&keypair, &equality_proof_keypair],
-        ).await
-        .map_err(|e| anyhow!("Failed to create equality proof account: {}", e))?;
-
-        // Create ciphertext validity proof context state (use split for safety)
-        token.confidential_transfer_create_context_state_account(
-            &ciphertext_validity_proof_keypair.pubkey(),
-            &keypair.pubkey(),
-            &proof_data.ciphertext_validity_proof_data_with_ciphertext.proof_data,
-            true, // split account creation and proof verification
-            &[&keypair, &ciphertext_validity_proof_keypair],
-        ).await
-        .map_err(|e| anyhow!("Failed to create ciphertext validity proof account: {}", e))?;
-
-        // Create range proof context state (split mode - range proofs are large)
-        token.confidential_transfer_create_context_state_account(
-            &range_proof_keypair.pubkey(),
-            &keypair.pubkey(),
-            &proof_data.range_proof_data,
-            true, // split account creation and proof verification
-            &[&keypair, &range_proof_keypair],
-        ).await
-        .map_err(|e| anyhow!("Failed to create range proof account: {}", e))?;
-
-        // Execute transfer with proof accounts
-        println!("  Executing transfer...");
-        let ciphertext_validity_proof_with_ciphertext = ProofAccountWithCiphertext {
-            context_state_account: ciphertext_validity_proof_keypair.pubkey(),
-            ciphertext_lo: proof_data.ciphertext_validity_proof_data_with_ciphertext.ciphertext_lo.into(),
-            ciphertext_hi: proof_data.ciphertext_validity_proof_data_with_ciphertext.ciphertext_hi.into(),
-        };
+/// Generates the split transfer proofs, verifies them into context state accounts and
+/// sends the transfer.
+#[allow(clippy::too_many_arguments)]
+async fn send_transfer(
+    source: &TransferSource<'_>,
+    journal: &mut DistributionJournal,
+    index: usize,
+    dest_ata: &Pubkey,
+    dest_elgamal_pubkey: &ElGamalPubkey,
+    source_available_balance: &ElGamalCiphertext,
+    source_decryptable: &AeCiphertext,
+    memo: Option<&str>,
+) -> Result<RpcClientResponse> {
+    let TransferSource {
+        token,
+        authority: keypair,
+        authority_keys,
+        source_ata,
+        auditor_elgamal_pubkey,
+        ..
+    } = source;
+    let amount = journal.entries[index].amount;
+
+    // Generate split proof data
+    status!("  Generating proofs...");
+    let proof_data = transfer_split_proof_data(
+        source_available_balance,
+        source_decryptable,
+        amount,
+        &authority_keys.elgamal_keypair,
+        &authority_keys.aes_key,
+        dest_elgamal_pubkey,
+        auditor_elgamal_pubkey.as_ref(),
+    ).map_err(|e| anyhow!("Failed to generate proof data: {:?}", e))?;
+
+    // Create context state accounts for proofs (split mode for large proofs)
+    let equality_proof_keypair = Keypair::new();
+    let ciphertext_validity_proof_keypair = Keypair::new();
+    let range_proof_keypair = Keypair::new();
 
-        token.confidential_transfer_transfer(
-            &source_ata,
-            &dest_ata,
-            &keypair.pubkey(),
-            Some(&equality_proof_keypair.pubkey()),
-            Some(&ciphertext_validity_proof_with_ciphertext),
-            Some(&range_proof_keypair.pubkey()),
-            recipient.amount,
-            None, // account_info
-            &authority_keys.elgamal_keypair,
-            &authority_keys.aes_key,
-            &dest_elgamal_pubkey,
-            auditor_elgamal_pubkey.as_ref(),
-            &[&keypair],
-        ).await
-        .map_err(|e| anyhow!("Failed to execute confidential transfer: {}", e))?;
-
-        // Close context state accounts to recover rent
-        println!("  Cleaning up proof accounts...");
-        token.confidential_transfer_close_context_state_account(
-            &equality_proof_keypair.pubkey(),
-            &source_ata,
-            &keypair.pubkey(),
-            &[&keypair],
-        ).await
-        .map_err(|e| anyhow!("Failed to close equality proof account: {}", e))?;
-
-        token.confidential_transfer_close_context_state_account(
-            &ciphertext_validity_proof_keypair.pubkey(),
-            &source_ata,
-            &keypair.pubkey(),
-            &[&keypair],
-        ).await
-        .map_err(|e| anyhow!("Failed to close ciphertext validity proof account: {}", e))?;
-
-        token.confidential_transfer_close_context_state_account(
-            &range_proof_keypair.pubkey(),
-            &source_ata,
-            &keypair.pubkey(),
-            &[&keypair],
-        ).await
-        .map_err(|e| anyhow!("Failed to close range proof account: {}", e))?;
-
-        current_balance -= recipient.amount;
-        println!("  Transfer complete");
-    }
-
-    println!("\nDistribution complete.");
-    println!("Remaining balance: {}", current_balance);
+    // Journal the pubkeys before creating the accounts so a crash can't orphan them
+    journal.entries[index].proof_accounts = vec![
+        equality_proof_keypair.pubkey().to_string(),
+        ciphertext_validity_proof_keypair.pubkey().to_string(),
+        range_proof_keypair.pubkey().to_string(),
+    ];
+    journal.save()?;
+
+    status!("  Creating proof context accounts...");
+
+    // Create equality proof context state (small enough for single tx)
+    let response = token.confidential_transfer_create_context_state_account(
+        &equality_proof_keypair.pubkey(),
+        &keypair.pubkey(),
+        &proof_data.equality_proof_data,
+        false,
+        &[*keypair, &equality_proof_keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to create equality proof account: {}", e))?;
+    journal.record_response(index, "create_equality_proof_account", response)?;
+
+    // Create ciphertext validity proof context state (use split for safety)
+    let response = token.confidential_transfer_create_context_state_account(
+        &ciphertext_validity_proof_keypair.pubkey(),
+        &keypair.pubkey(),
+        &proof_data.ciphertext_validity_proof_data_with_ciphertext.proof_data,
+        true, // split account creation and proof verification
+        &[*keypair, &ciphertext_validity_proof_keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to create ciphertext validity proof account: {}", e))?;
+    journal.record_response(index, "create_ciphertext_validity_proof_account", response)?;
+
+    // Create range proof context state (split mode - range proofs are large)
+    let response = token.confidential_transfer_create_context_state_account(
+        &range_proof_keypair.pubkey(),
+        &keypair.pubkey(),
+        &proof_data.range_proof_data,
+        true, // split account creation and proof verification
+        &[*keypair, &range_proof_keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to create range proof account: {}", e))?;
+    journal.record_response(index, "create_range_proof_account", response)?;
+    journal.set_status(index, TransferStatus::ProofAccountsCreated)?;
+
+    // Execute transfer with proof accounts
+    status!("  Executing transfer...");
+    let ciphertext_validity_proof_with_ciphertext = ProofAccountWithCiphertext {
+        context_state_account: ciphertext_validity_proof_keypair.pubkey(),
+        ciphertext_lo: proof_data.ciphertext_validity_proof_data_with_ciphertext.ciphertext_lo.into(),
+        ciphertext_hi: proof_data.ciphertext_validity_proof_data_with_ciphertext.ciphertext_hi.into(),
+    };
+
+    // The memo is attached to the next transaction the client sends
+    if let Some(memo) = memo {
+        token.with_memo(memo, vec![keypair.pubkey()]);
+    }
+
+    token.confidential_transfer_transfer(
+        source_ata,
+        dest_ata,
+        &keypair.pubkey(),
+        Some(&equality_proof_keypair.pubkey()),
+        Some(&ciphertext_validity_proof_with_ciphertext),
+        Some(&range_proof_keypair.pubkey()),
+        amount,
+        None, // account_info
+        &authority_keys.elgamal_keypair,
+        &authority_keys.aes_key,
+        dest_elgamal_pubkey,
+        auditor_elgamal_pubkey.as_ref(),
+        &[*keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to execute confidential transfer: {}", e))
+}
+
+/// Like [`send_transfer`], for mints with a confidential transfer fee: adds the
+/// percentage-with-cap and fee ciphertext validity proofs.
+#[allow(clippy::too_many_arguments)]
+async fn send_transfer_with_fee(
+    source: &TransferSource<'_>,
+    journal: &mut DistributionJournal,
+    index: usize,
+    fee: &MintTransferFee,
+    dest_ata: &Pubkey,
+    dest_elgamal_pubkey: &ElGamalPubkey,
+    source_available_balance: &ElGamalCiphertext,
+    source_decryptable: &AeCiphertext,
+    memo: Option<&str>,
+) -> Result<RpcClientResponse> {
+    let TransferSource {
+        token,
+        authority: keypair,
+        authority_keys,
+        source_ata,
+        auditor_elgamal_pubkey,
+        ..
+    } = source;
+    let amount = journal.entries[index].amount;
+
+    status!("  Generating proofs (with fee)...");
+    let proof_data = transfer_with_fee_split_proof_data(
+        source_available_balance,
+        source_decryptable,
+        amount,
+        &authority_keys.elgamal_keypair,
+        &authority_keys.aes_key,
+        dest_elgamal_pubkey,
+        auditor_elgamal_pubkey.as_ref(),
+        &fee.withdraw_withheld_authority_elgamal_pubkey,
+        fee.basis_points,
+        fee.maximum_fee,
+    ).map_err(|e| anyhow!("Failed to generate proof data: {:?}", e))?;
+
+    let equality_proof_keypair = Keypair::new();
+    let transfer_amount_validity_proof_keypair = Keypair::new();
+    let percentage_with_cap_proof_keypair = Keypair::new();
+    let fee_validity_proof_keypair = Keypair::new();
+    let range_proof_keypair = Keypair::new();
+
+    // Journal the pubkeys before creating the accounts so a crash can't orphan them
+    journal.entries[index].proof_accounts = vec![
+        equality_proof_keypair.pubkey().to_string(),
+        transfer_amount_validity_proof_keypair.pubkey().to_string(),
+        percentage_with_cap_proof_keypair.pubkey().to_string(),
+        fee_validity_proof_keypair.pubkey().to_string(),
+        range_proof_keypair.pubkey().to_string(),
+    ];
+    journal.save()?;
+
+    status!("  Creating proof context accounts...");
+
+    let response = token.confidential_transfer_create_context_state_account(
+        &equality_proof_keypair.pubkey(),
+        &keypair.pubkey(),
+        &proof_data.equality_proof_data,
+        false,
+        &[*keypair, &equality_proof_keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to create equality proof account: {}", e))?;
+    journal.record_response(index, "create_equality_proof_account", response)?;
+
+    let response = token.confidential_transfer_create_context_state_account(
+        &transfer_amount_validity_proof_keypair.pubkey(),
+        &keypair.pubkey(),
+        &proof_data.transfer_amount_ciphertext_validity_proof_data_with_ciphertext.proof_data,
+        true, // split account creation and proof verification
+        &[*keypair, &transfer_amount_validity_proof_keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to create transfer amount validity proof account: {}", e))?;
+    journal.record_response(index, "create_ciphertext_validity_proof_account", response)?;
+
+    let response = token.confidential_transfer_create_context_state_account(
+        &percentage_with_cap_proof_keypair.pubkey(),
+        &keypair.pubkey(),
+        &proof_data.percentage_with_cap_proof_data,
+        false,
+        &[*keypair, &percentage_with_cap_proof_keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to create percentage-with-cap proof account: {}", e))?;
+    journal.record_response(index, "create_percentage_with_cap_proof_account", response)?;
+
+    let response = token.confidential_transfer_create_context_state_account(
+        &fee_validity_proof_keypair.pubkey(),
+        &keypair.pubkey(),
+        &proof_data.fee_ciphertext_validity_proof_data,
+        true, // split account creation and proof verification
+        &[*keypair, &fee_validity_proof_keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to create fee validity proof account: {}", e))?;
+    journal.record_response(index, "create_fee_validity_proof_account", response)?;
+
+    // Range proofs over amount, fee and their sum are the largest; always split
+    let response = token.confidential_transfer_create_context_state_account(
+        &range_proof_keypair.pubkey(),
+        &keypair.pubkey(),
+        &proof_data.range_proof_data,
+        true, // split account creation and proof verification
+        &[*keypair, &range_proof_keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to create range proof account: {}", e))?;
+    journal.record_response(index, "create_range_proof_account", response)?;
+    journal.set_status(index, TransferStatus::ProofAccountsCreated)?;
+
+    status!("  Executing transfer...");
+    let transfer_amount_validity_proof_with_ciphertext = ProofAccountWithCiphertext {
+        context_state_account: transfer_amount_validity_proof_keypair.pubkey(),
+        ciphertext_lo: proof_data.transfer_amount_ciphertext_validity_proof_data_with_ciphertext.ciphertext_lo.into(),
+        ciphertext_hi: proof_data.transfer_amount_ciphertext_validity_proof_data_with_ciphertext.ciphertext_hi.into(),
+    };
+
+    // The memo is attached to the next transaction the client sends
+    if let Some(memo) = memo {
+        token.with_memo(memo, vec![keypair.pubkey()]);
+    }
+
+    token.confidential_transfer_transfer_with_fee(
+        source_ata,
+        dest_ata,
+        &keypair.pubkey(),
+        Some(&equality_proof_keypair.pubkey()),
+        Some(&transfer_amount_validity_proof_with_ciphertext),
+        Some(&percentage_with_cap_proof_keypair.pubkey()),
+        Some(&fee_validity_proof_keypair.pubkey()),
+        Some(&range_proof_keypair.pubkey()),
+        amount,
+        None, // account_info
+        &authority_keys.elgamal_keypair,
+        &authority_keys.aes_key,
+        dest_elgamal_pubkey,
+        auditor_elgamal_pubkey.as_ref(),
+        &fee.withdraw_withheld_authority_elgamal_pubkey,
+        fee.basis_points,
+        fee.maximum_fee,
+        &[*keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to execute confidential transfer with fee: {}", e))
+}
+
+/// Closes every still-open proof context account recorded for a journal entry.
+async fn close_proof_accounts(
+    source: &TransferSource<'_>,
+    journal: &mut DistributionJournal,
+    index: usize,
+) -> Result<()> {
+    let proof_accounts = journal.entries[index].proof_accounts.clone();
+
+    for account in proof_accounts {
+        let pubkey = Pubkey::from_str(&account)
+            .with_context(|| format!("Invalid proof account in journal: {}", account))?;
+
+        // Accounts that were never created (or already closed) have nothing to reclaim
+        if source.rpc_client.get_account(&pubkey).await.is_ok() {
+            let response = source.token.confidential_transfer_close_context_state_account(
+                &pubkey,
+                &source.source_ata,
+                &source.authority.pubkey(),
+                &[source.authority],
+            ).await
+            .map_err(|e| anyhow!("Failed to close proof account {}: {}", pubkey, e))?;
+            journal.record_response(index, "close_proof_account", response)?;
+        }
+    }
+
+    journal.entries[index].proof_accounts.clear();
+    journal.save()
+}
+
+/// What an entry left behind by an interrupted run still needs.
+#[derive(Debug, PartialEq, Eq)]
+enum Recovery {
+    /// Settled, or never got as far as sending anything
+    Nothing,
+    /// The transfer landed; its proof accounts still need closing
+    FinishCleanup,
+    /// The transfer did not land; close its proof accounts and send it again
+    Retry,
+    /// The source balance moved by something other than this transfer
+    Unknown,
+}
+
+/// Decides how to recover an entry from its journaled state and, for entries that may
+/// have sent their transfer, the source's current decryptable balance.
+fn recovery(entry: &JournalEntry, current_balance: Option<u64>) -> Recovery {
+    match entry.status {
+        TransferStatus::Completed | TransferStatus::Parked => Recovery::Nothing,
+        TransferStatus::Transferred => Recovery::FinishCleanup,
+        TransferStatus::Pending | TransferStatus::ProofAccountsCreated => {
+            let (Some(before), Some(current)) = (entry.source_balance_before, current_balance)
+            else {
+                return Recovery::Nothing;
+            };
+
+            // The source's decryptable balance only moves when the transfer lands
+            if Some(current) == before.checked_sub(entry.amount) {
+                Recovery::FinishCleanup
+            } else if current == before {
+                Recovery::Retry
+            } else {
+                Recovery::Unknown
+            }
+        }
+    }
+}
+
+/// Brings a journal entry left behind by an interrupted run to a clean state: either the
+/// transfer landed (finish cleanup) or it didn't (close its proof accounts and retry).
+async fn recover_entry(
+    source: &TransferSource<'_>,
+    journal: &mut DistributionJournal,
+    index: usize,
+) -> Result<()> {
+    let entry = &journal.entries[index];
+    let (wallet, status, before) = (entry.wallet.clone(), entry.status, entry.source_balance_before);
+
+    let may_have_sent = matches!(
+        status,
+        TransferStatus::Pending | TransferStatus::ProofAccountsCreated
+    ) && before.is_some();
+    let current_balance = if may_have_sent {
+        Some(fetch_source_balance(source).await?)
+    } else {
+        None
+    };
+
+    match recovery(&journal.entries[index], current_balance) {
+        Recovery::Nothing => Ok(()),
+        Recovery::FinishCleanup => {
+            if status == TransferStatus::Transferred {
+                status!("Finishing cleanup for transfer to {}", wallet);
+            } else {
+                status!("Transfer to {} landed before interruption", wallet);
+                journal.set_status(index, TransferStatus::Transferred)?;
+            }
+            close_proof_accounts(source, journal, index).await?;
+            journal.entries[index].error = None;
+            journal.set_status(index, TransferStatus::Completed)
+        }
+        Recovery::Retry => {
+            status!("Transfer to {} did not land, cleaning up and retrying", wallet);
+            close_proof_accounts(source, journal, index).await?;
+            journal.entries[index].source_balance_before = None;
+            journal.entries[index].error = None;
+            journal.set_status(index, TransferStatus::Pending)
+        }
+        Recovery::Unknown => Err(anyhow!(
+            "Cannot tell whether the transfer to {} landed: balance was {} before, now {}",
+            wallet,
+            before.unwrap_or_default(),
+            current_balance.unwrap_or_default()
+        )),
+    }
+}
+
+/// Whether a recipient can receive a confidential transfer right now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Readiness {
+    Ready,
+    InvalidPubkey,
+    AtaMissing,
+    NotConfigured,
+    NotApproved,
+    CreditsDisabled,
+    CreditLimitReached,
+}
+
+impl Readiness {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Readiness::Ready => "ready",
+            Readiness::InvalidPubkey => "invalid pubkey",
+            Readiness::AtaMissing => "ATA missing",
+            Readiness::NotConfigured => "not configured",
+            Readiness::NotApproved => "not approved",
+            Readiness::CreditsDisabled => "credits disabled",
+            Readiness::CreditLimitReached => "pending credit limit reached",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Readiness> {
+        [
+            Readiness::Ready,
+            Readiness::InvalidPubkey,
+            Readiness::AtaMissing,
+            Readiness::NotConfigured,
+            Readiness::NotApproved,
+            Readiness::CreditsDisabled,
+            Readiness::CreditLimitReached,
+        ]
+        .into_iter()
+        .find(|readiness| readiness.label() == label)
+    }
+}
+
+/// Classifies each wallet by fetching its ATA for `mint` in batches, without sending
+/// any transactions.
+pub async fn check_readiness(
+    rpc_client: &NonblockingRpcClient,
+    mint: &Pubkey,
+    wallets: &[String],
+) -> Result<Vec<Readiness>> {
+    let mut results = vec![Readiness::InvalidPubkey; wallets.len()];
+
+    let atas: Vec<(usize, Pubkey)> = wallets
+        .iter()
+        .enumerate()
+        .filter_map(|(i, wallet)| {
+            let wallet = Pubkey::from_str(wallet).ok()?;
+            Some((
+                i,
+                get_associated_token_address_with_program_id(&wallet, mint, &spl_token_2022::id()),
+            ))
+        })
+        .collect();
+
+    // getMultipleAccounts accepts at most 100 keys per request
+    for chunk in atas.chunks(100) {
+        let keys: Vec<Pubkey> = chunk.iter().map(|(_, ata)| *ata).collect();
+        let accounts = rpc_client
+            .get_multiple_accounts(&keys)
+            .await
+            .context("Failed to fetch recipient token accounts")?;
+
+        for ((i, _), account) in chunk.iter().zip(accounts) {
+            results[*i] = match account {
+                None => Readiness::AtaMissing,
+                Some(account) => classify_account(&account.data),
+            };
+        }
+    }
+
+    Ok(results)
+}
+
+fn classify_account(data: &[u8]) -> Readiness {
+    let Ok(state) = StateWithExtensions::<spl_token_2022::state::Account>::unpack(data) else {
+        return Readiness::NotConfigured;
+    };
+    let Ok(ct) = state.get_extension::<ConfidentialTransferAccount>() else {
+        return Readiness::NotConfigured;
+    };
+
+    if !bool::from(ct.approved) {
+        Readiness::NotApproved
+    } else if !bool::from(ct.allow_confidential_credits) {
+        Readiness::CreditsDisabled
+    } else if u64::from(ct.pending_balance_credit_counter)
+        >= u64::from(ct.maximum_pending_balance_credit_counter)
+    {
+        Readiness::CreditLimitReached
+    } else {
+        Readiness::Ready
+    }
+}
+
+fn write_readiness_report(
+    path: &std::path::Path,
+    entries: &[(String, u64, Readiness)],
+) -> Result<()> {
+    let mut writer = Writer::from_path(path)
+        .with_context(|| format!("Failed to write report: {}", path.display()))?;
+    writer.write_record(["wallet", "amount", "status"])?;
+    for (wallet, amount, readiness) in entries {
+        writer.write_record([wallet.as_str(), amount.to_string().as_str(), readiness.label()])?;
+    }
+    writer.flush()?;
     Ok(())
 }
 
-fn parse_recipients(path: &std::path::Path) -> Result<Vec<Recipient>> {
+/// Rows already in the queue at `path` followed by `parked`. A wallet parked again takes
+/// its new row.
+fn merge_parked(
+    path: &std::path::Path,
+    parked: &[(String, u64, Readiness)],
+) -> Result<Vec<(String, u64, Readiness)>> {
+    if !path.exists() {
+        return Ok(parked.to_vec());
+    }
+
+    #[derive(Deserialize)]
+    struct QueueRow {
+        wallet: String,
+        amount: u64,
+        status: String,
+    }
+
+    let mut reader = Reader::from_path(path)
+        .with_context(|| format!("Failed to read pending queue: {}", path.display()))?;
+    let mut merged = Vec::new();
+    for result in reader.deserialize() {
+        let row: QueueRow = result.context("Failed to parse pending queue row")?;
+        if parked.iter().any(|(wallet, _, _)| *wallet == row.wallet) {
+            continue;
+        }
+        let readiness = Readiness::from_label(&row.status).ok_or_else(|| {
+            anyhow!("Unknown status '{}' in pending queue {}", row.status, path.display())
+        })?;
+        merged.push((row.wallet, row.amount, readiness));
+    }
+    merged.extend_from_slice(parked);
+    Ok(merged)
+}
+
+/// Writes a self-serve guide for parked recipients. Amounts are deliberately left out so
+/// the file can be shared without revealing allocations.
+fn write_onboarding_instructions(
+    path: &std::path::Path,
+    mint: &Pubkey,
+    rpc: &str,
+    parked: &[(String, u64, Readiness)],
+) -> Result<()> {
+    let mut out = String::new();
+    out.push_str(&format!("# Confidential transfer onboarding for {}\n\n", mint));
+    out.push_str("You have a pending allocation of this token. It can only be delivered to a token account\n");
+    out.push_str("that is configured for confidential transfers. Find your wallet below and follow its action,\n");
+    out.push_str("then let the distributor know so they can retry.\n\n");
+    out.push_str("To configure your account (needs your own keypair, which never leaves your machine):\n\n");
+    out.push_str(&format!(
+        "```bash\nstealth-launch configure --mint {} --owner <YOUR_KEYPAIR.json> --rpc {}\n```\n\n",
+        mint, rpc
+    ));
+    out.push_str("| Wallet | Status | Action |\n|--------|--------|--------|\n");
+
+    for (wallet, _, readiness) in parked {
+        let action = match readiness {
+            Readiness::AtaMissing | Readiness::NotConfigured => "Run `configure` (above)".to_string(),
+            Readiness::NotApproved => {
+                "Ask the mint's confidential transfer authority to approve your account".to_string()
+            }
+            Readiness::CreditsDisabled => format!("Run `stealth-launch enable-credits --mint {}`", mint),
+            Readiness::CreditLimitReached => format!("Run `stealth-launch apply-pending --mint {}`", mint),
+            Readiness::Ready | Readiness::InvalidPubkey => continue,
+        };
+        out.push_str(&format!("| `{}` | {} | {} |\n", wallet, readiness.label(), action));
+    }
+
+    fs::write(path, out)
+        .with_context(|| format!("Failed to write onboarding instructions: {}", path.display()))
+}
+
+pub fn parse_recipients(path: &std::path::Path) -> Result<Vec<Recipient>> {
     let mut reader = Reader::from_path(path)
         .with_context(|| format!("Failed to read CSV file: {}", path.display()))?;
 
     let mut recipients = Vec::new();
-    for result in reader.deserialize() {
+    for (row, result) in reader.deserialize().enumerate() {
         let recipient: Recipient = result.context("Failed to parse recipient row")?;
+        // Recovery tells whether a transfer landed from the drop in the source balance,
+        // which a zero amount never causes
+        if recipient.amount == 0 {
+            anyhow::bail!(
+                "Row {} ({}): amount must be greater than zero",
+                row + 1,
+                recipient.wallet
+            );
+        }
         recipients.push(recipient);
     }
 
@@ -263,3 +1355,178 @@ fn parse_recipients(path: &std::path::Path) -> Result<Vec<Recipient>> {
 
     Ok(recipients)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spl_token_2022::extension::{
+        BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+    };
+    use spl_token_2022::state::{Account, AccountState};
+
+    /// Writes `contents` to a CSV file unique to this test.
+    fn recipients_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}.csv", name, std::process::id()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn parses_recipients() {
+        let path = recipients_file("recipients-valid", "wallet,amount\nA,10\nB,20\n");
+        let recipients = parse_recipients(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(recipients.len(), 2);
+        assert_eq!((recipients[1].wallet.as_str(), recipients[1].amount), ("B", 20));
+    }
+
+    #[test]
+    fn rejects_zero_amount_rows() {
+        let path = recipients_file("recipients-zero", "wallet,amount\nA,10\nB,0\n");
+        let error = parse_recipients(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+
+        assert!(error.to_string().contains("Row 2"), "{}", error);
+    }
+
+    fn entry(status: TransferStatus, source_balance_before: Option<u64>) -> JournalEntry {
+        JournalEntry {
+            status,
+            source_balance_before,
+            ..JournalEntry::new("wallet".to_string(), 100)
+        }
+    }
+
+    #[test]
+    fn settled_entries_need_nothing() {
+        for status in [TransferStatus::Completed, TransferStatus::Parked] {
+            assert_eq!(recovery(&entry(status, Some(1_000)), Some(900)), Recovery::Nothing);
+        }
+    }
+
+    #[test]
+    fn transferred_entry_finishes_cleanup() {
+        assert_eq!(
+            recovery(&entry(TransferStatus::Transferred, Some(1_000)), None),
+            Recovery::FinishCleanup
+        );
+    }
+
+    #[test]
+    fn entry_without_recorded_balance_was_never_sent() {
+        for status in [TransferStatus::Pending, TransferStatus::ProofAccountsCreated] {
+            assert_eq!(recovery(&entry(status, None), None), Recovery::Nothing);
+        }
+    }
+
+    #[test]
+    fn balance_drop_by_amount_means_transfer_landed() {
+        for status in [TransferStatus::Pending, TransferStatus::ProofAccountsCreated] {
+            assert_eq!(
+                recovery(&entry(status, Some(1_000)), Some(900)),
+                Recovery::FinishCleanup
+            );
+        }
+    }
+
+    #[test]
+    fn unchanged_balance_means_retry() {
+        assert_eq!(
+            recovery(&entry(TransferStatus::ProofAccountsCreated, Some(1_000)), Some(1_000)),
+            Recovery::Retry
+        );
+    }
+
+    #[test]
+    fn unexpected_balance_is_unknown() {
+        let entry = entry(TransferStatus::ProofAccountsCreated, Some(1_000));
+        assert_eq!(recovery(&entry, Some(950)), Recovery::Unknown);
+        assert_eq!(recovery(&entry, Some(1_100)), Recovery::Unknown);
+    }
+
+    /// Token account data, with the confidential transfer extension if `ct` is given.
+    fn token_account(ct: Option<ConfidentialTransferAccount>) -> Vec<u8> {
+        let extensions = match ct {
+            Some(_) => vec![ExtensionType::ConfidentialTransferAccount],
+            None => vec![],
+        };
+        let mut data =
+            vec![0; ExtensionType::try_calculate_account_len::<Account>(&extensions).unwrap()];
+
+        let mut state = StateWithExtensionsMut::<Account>::unpack_uninitialized(&mut data).unwrap();
+        if let Some(ct) = ct {
+            *state.init_extension::<ConfidentialTransferAccount>(true).unwrap() = ct;
+        }
+        state.base = Account {
+            state: AccountState::Initialized,
+            ..Default::default()
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        data
+    }
+
+    fn ready_extension() -> ConfidentialTransferAccount {
+        ConfidentialTransferAccount {
+            approved: true.into(),
+            allow_confidential_credits: true.into(),
+            pending_balance_credit_counter: 0u64.into(),
+            maximum_pending_balance_credit_counter: 65_536u64.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn configured_account_is_ready() {
+        assert_eq!(classify_account(&token_account(Some(ready_extension()))), Readiness::Ready);
+    }
+
+    #[test]
+    fn account_without_extension_is_not_configured() {
+        assert_eq!(classify_account(&token_account(None)), Readiness::NotConfigured);
+        assert_eq!(classify_account(&[0; 10]), Readiness::NotConfigured);
+    }
+
+    #[test]
+    fn unapproved_account_is_not_approved() {
+        let ct = ConfidentialTransferAccount {
+            approved: false.into(),
+            ..ready_extension()
+        };
+        assert_eq!(classify_account(&token_account(Some(ct))), Readiness::NotApproved);
+    }
+
+    #[test]
+    fn account_with_credits_disabled() {
+        let ct = ConfidentialTransferAccount {
+            allow_confidential_credits: false.into(),
+            ..ready_extension()
+        };
+        assert_eq!(classify_account(&token_account(Some(ct))), Readiness::CreditsDisabled);
+    }
+
+    #[test]
+    fn account_at_pending_credit_limit() {
+        let ct = ConfidentialTransferAccount {
+            pending_balance_credit_counter: 65_536u64.into(),
+            ..ready_extension()
+        };
+        assert_eq!(classify_account(&token_account(Some(ct))), Readiness::CreditLimitReached);
+    }
+
+    #[test]
+    fn readiness_labels_round_trip() {
+        for readiness in [
+            Readiness::Ready,
+            Readiness::InvalidPubkey,
+            Readiness::AtaMissing,
+            Readiness::NotConfigured,
+            Readiness::NotApproved,
+            Readiness::CreditsDisabled,
+            Readiness::CreditLimitReached,
+        ] {
+            assert_eq!(Readiness::from_label(readiness.label()), Some(readiness));
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::cli::EnableCreditsArgs;
+use crate::compute_budget;
+use crate::config::{create_rpc_client, expand_path, load_keypair};
+use anyhow::{Context, Result};
+use solana_sdk::signature::Signer;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::extension::{
+    confidential_transfer::{instruction::enable_confidential_credits, ConfidentialTransferAccount},
+    BaseStateWithExtensions, StateWithExtensions,
+};
+
+pub async fn execute(args: EnableCreditsArgs) -> Result<()> {
+    let keypair = load_keypair(&expand_path(&args.keypair))?;
+    let rpc = create_rpc_client(&args.rpc);
+
+    let ata = get_associated_token_address_with_program_id(
+        &keypair.pubkey(),
+        &args.mint,
+        &spl_token_2022::id(),
+    );
+
+    status!("Enabling confidential credits...");
+    status!("  Mint: {}", args.mint);
+    status!("  Token Account: {}", ata);
+
+    let account_data = rpc
+        .get_account(&ata)
+        .with_context(|| format!("Token account not found for wallet {}", keypair.pubkey()))?;
+    let account_state =
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)
+            .context("Failed to unpack token account")?;
+    let ct_account = account_state
+        .get_extension::<ConfidentialTransferAccount>()
+        .context("Confidential transfer extension not found on account - run configure first")?;
+
+    if bool::from(ct_account.allow_confidential_credits) {
+        status!("\nConfidential credits are already enabled.");
+        return Ok(());
+    }
+
+    let enable_ix = enable_confidential_credits(
+        &spl_token_2022::id(),
+        &ata,
+        &keypair.pubkey(),
+        &[],
+    )?;
+
+    let tx = compute_budget::signed_transaction(
+        &rpc,
+        &[enable_ix],
+        &keypair.pubkey(),
+        &[&keypair],
+    )?;
+
+    let signature = rpc.send_and_confirm_transaction_with_spinner(&tx)
+        .context("Failed to enable confidential credits")?;
+
+    status!("\nConfidential credits enabled.");
+    status!("Signature: {}", signature);
+
+    Ok(())
+}
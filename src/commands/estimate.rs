@@ -0,0 +1,343 @@
+use crate::cli::{CreateArgs, EstimateArgs, EstimateCommands, EstimateDistributeArgs};
+use crate::commands::create::mint_account_space;
+use crate::commands::distribute::parse_recipients;
+use crate::compute_budget;
+use crate::config::{create_rpc_client, expand_path, load_keypair};
+use crate::output;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    message::Message,
+    native_token::LAMPORTS_PER_SOL,
+    signature::Signer,
+};
+use spl_token_2022::{
+    extension::{transfer_fee::TransferFeeConfig, ExtensionType, StateWithExtensions},
+    solana_zk_sdk::zk_elgamal_proof_program::{
+        proof_data::{
+            BatchedGroupedCiphertext2HandlesValidityProofContext,
+            BatchedGroupedCiphertext3HandlesValidityProofContext, BatchedRangeProofContext,
+            CiphertextCommitmentEqualityProofContext, PercentageWithCapProofContext,
+        },
+        state::ProofContextState,
+    },
+    state::{Account, Mint},
+};
+use std::mem::size_of;
+
+/// Compute units a transaction is charged priority fees for without
+/// `--compute-unit-limit`; the runtime default for a single instruction.
+const DEFAULT_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+
+/// One step of a launch or distribution, possibly repeated, and what it costs.
+#[derive(Serialize)]
+struct CostLine {
+    step: &'static str,
+    transactions: u64,
+    signatures: u64,
+    /// Rent left in accounts that stay open
+    rent: u64,
+    /// Rent deposited into proof context accounts, refunded when they are closed
+    refundable_rent: u64,
+}
+
+/// Result document for `--output json`.
+#[derive(Serialize)]
+struct Estimate {
+    payer: String,
+    lines: Vec<CostLine>,
+    signature_fees: u64,
+    priority_fees: u64,
+    rent: u64,
+    /// Most refundable rent held at any one time; proof accounts are closed after each use
+    peak_refundable_rent: u64,
+    required: u64,
+    payer_balance: u64,
+    sufficient: bool,
+}
+
+/// A proof context account as `Token::confidential_transfer_create_context_state_account`
+/// creates it: `split` proofs are too large to verify in the creating transaction and
+/// take a second transaction.
+struct ProofAccount {
+    space: usize,
+    split: bool,
+}
+
+const EQUALITY_PROOF_ACCOUNT: ProofAccount = ProofAccount {
+    space: size_of::<ProofContextState<CiphertextCommitmentEqualityProofContext>>(),
+    split: false,
+};
+const VALIDITY_PROOF_ACCOUNT: ProofAccount = ProofAccount {
+    space: size_of::<ProofContextState<BatchedGroupedCiphertext3HandlesValidityProofContext>>(),
+    split: true,
+};
+const FEE_VALIDITY_PROOF_ACCOUNT: ProofAccount = ProofAccount {
+    space: size_of::<ProofContextState<BatchedGroupedCiphertext2HandlesValidityProofContext>>(),
+    split: true,
+};
+const PERCENTAGE_WITH_CAP_PROOF_ACCOUNT: ProofAccount = ProofAccount {
+    space: size_of::<ProofContextState<PercentageWithCapProofContext>>(),
+    split: false,
+};
+const RANGE_PROOF_ACCOUNT: ProofAccount = ProofAccount {
+    space: size_of::<ProofContextState<BatchedRangeProofContext>>(),
+    split: true,
+};
+
+pub async fn execute(args: EstimateArgs) -> Result<()> {
+    let (keypair_path, rpc_url) = match &args.command {
+        EstimateCommands::Create(create) => (&create.keypair, &create.rpc),
+        EstimateCommands::Distribute(distribute) => (&distribute.keypair, &distribute.rpc),
+    };
+    let payer = load_keypair(&expand_path(keypair_path))?.pubkey();
+    let rpc = create_rpc_client(rpc_url);
+
+    let lines = match &args.command {
+        EstimateCommands::Create(create) => create_cost(&rpc, create)?,
+        EstimateCommands::Distribute(distribute) => distribute_cost(&rpc, distribute)?,
+    };
+
+    // The base fee is charged per signature; an empty message carries just the payer's
+    let lamports_per_signature = rpc
+        .get_fee_for_message(&Message::new(&[], Some(&payer)))
+        .context("Failed to fetch the signature fee")?;
+    let compute_unit_price = compute_budget::compute_unit_price(&rpc, &[payer])?.unwrap_or_default();
+    let compute_unit_limit =
+        compute_budget::compute_unit_limit().unwrap_or(DEFAULT_COMPUTE_UNIT_LIMIT);
+    let priority_fee_per_transaction = u64::from(compute_unit_limit)
+        .checked_mul(compute_unit_price)
+        .context("Priority fee overflows - lower --priority-fee or --compute-unit-limit")?
+        .div_ceil(1_000_000);
+
+    let transactions: u64 = lines.iter().map(|l| l.transactions).sum();
+    let signature_fees = lines
+        .iter()
+        .map(|l| l.signatures)
+        .sum::<u64>()
+        .checked_mul(lamports_per_signature)
+        .context("Signature fees overflow")?;
+    let priority_fees = transactions
+        .checked_mul(priority_fee_per_transaction)
+        .context("Priority fees overflow - lower --priority-fee or --compute-unit-limit")?;
+    let rent: u64 = lines.iter().map(|l| l.rent).sum();
+    let peak_refundable_rent = lines.iter().map(|l| l.refundable_rent).max().unwrap_or_default();
+    let required = signature_fees
+        .checked_add(priority_fees)
+        .and_then(|total| total.checked_add(rent))
+        .and_then(|total| total.checked_add(peak_refundable_rent))
+        .context("Estimated cost overflows")?;
+    let payer_balance = rpc.get_balance(&payer)?;
+
+    status!("Estimated cost (payer {}):", payer);
+    status!("  {:<28} {:>6} {:>6} {:>14} {:>16}", "step", "txs", "sigs", "rent", "refundable rent");
+    for line in &lines {
+        status!(
+            "  {:<28} {:>6} {:>6} {:>14} {:>16}",
+            line.step,
+            line.transactions,
+            line.signatures,
+            line.rent,
+            line.refundable_rent
+        );
+    }
+    status!("");
+    status!("Transactions: {}", transactions);
+    status!("Signature fees: {} lamports ({} per signature)", signature_fees, lamports_per_signature);
+    if compute_unit_price > 0 {
+        status!(
+            "Priority fees: {} lamports ({} micro-lamports x {} compute units per transaction)",
+            priority_fees,
+            compute_unit_price,
+            compute_unit_limit
+        );
+    }
+    status!("Rent: {} lamports", rent);
+    status!("Refundable rent held at once: {} lamports", peak_refundable_rent);
+    status!("Required: {:.9} SOL", required as f64 / LAMPORTS_PER_SOL as f64);
+    status!("Payer balance: {:.9} SOL", payer_balance as f64 / LAMPORTS_PER_SOL as f64);
+
+    let sufficient = payer_balance >= required;
+    if sufficient {
+        status!("\nPayer balance covers the estimate.");
+    } else {
+        status!(
+            "\nPayer is short by {:.9} SOL.",
+            (required - payer_balance) as f64 / LAMPORTS_PER_SOL as f64
+        );
+    }
+
+    output::emit(&Estimate {
+        payer: payer.to_string(),
+        lines,
+        signature_fees,
+        priority_fees,
+        rent,
+        peak_refundable_rent,
+        required,
+        payer_balance,
+        sufficient,
+    })
+}
+
+/// Every transaction of a fresh `create` launch with these arguments.
+fn create_cost(rpc: &RpcClient, args: &CreateArgs) -> Result<Vec<CostLine>> {
+    let transfer_fee = args.fee_basis_points.is_some();
+    if args.resume.is_some() {
+        status!("Estimating a full launch; steps a resumed launch already finished are included");
+    }
+
+    let (space, metadata_space) = mint_account_space(
+        &args.name,
+        &args.symbol,
+        &args.uri,
+        args.confidential_supply,
+        transfer_fee,
+    )?;
+
+    // The mint keypair signs its own creation
+    let mut lines = vec![
+        CostLine {
+            step: "create_mint",
+            transactions: 1,
+            signatures: 2,
+            rent: rpc.get_minimum_balance_for_rent_exemption(space + metadata_space)?,
+            refundable_rent: 0,
+        },
+        CostLine {
+            step: "create_token_account",
+            transactions: 1,
+            signatures: 1,
+            rent: token_account_rent(rpc, transfer_fee)?,
+            refundable_rent: 0,
+        },
+        single_transaction("reallocate_token_account"),
+        single_transaction("configure_token_account"),
+    ];
+
+    if args.require_approval {
+        lines.push(single_transaction("approve_account"));
+    }
+
+    if args.supply > 0 {
+        if args.confidential_supply {
+            let proof_accounts =
+                [EQUALITY_PROOF_ACCOUNT, VALIDITY_PROOF_ACCOUNT, RANGE_PROOF_ACCOUNT];
+            lines.push(create_proof_accounts(rpc, &proof_accounts, 1)?);
+            lines.push(single_transaction("confidential_mint"));
+            lines.push(close_proof_accounts(&proof_accounts, 1));
+        } else {
+            lines.push(single_transaction("mint_to"));
+            lines.push(single_transaction("deposit"));
+        }
+        lines.push(single_transaction("apply_pending_balance"));
+    }
+
+    Ok(lines)
+}
+
+/// Every transaction of a `distribute` run paying each row of the recipients CSV.
+fn distribute_cost(rpc: &RpcClient, args: &EstimateDistributeArgs) -> Result<Vec<CostLine>> {
+    let recipients = parse_recipients(&expand_path(&args.recipients))?.len() as u64;
+
+    let mint_account = rpc
+        .get_account(&args.mint)
+        .with_context(|| format!("Mint {} not found", args.mint))?;
+    let mint_state =
+        StateWithExtensions::<Mint>::unpack(&mint_account.data).context("Failed to unpack mint")?;
+
+    // Fee-bearing mints need the transfer-with-fee proofs (two extra context accounts)
+    let proof_accounts = if mint_state.get_extension::<TransferFeeConfig>().is_ok() {
+        vec![
+            EQUALITY_PROOF_ACCOUNT,
+            VALIDITY_PROOF_ACCOUNT,
+            PERCENTAGE_WITH_CAP_PROOF_ACCOUNT,
+            FEE_VALIDITY_PROOF_ACCOUNT,
+            RANGE_PROOF_ACCOUNT,
+        ]
+    } else {
+        vec![EQUALITY_PROOF_ACCOUNT, VALIDITY_PROOF_ACCOUNT, RANGE_PROOF_ACCOUNT]
+    };
+
+    status!("Estimating {} transfers", recipients);
+
+    Ok(vec![
+        create_proof_accounts(rpc, &proof_accounts, recipients)?,
+        CostLine {
+            step: "transfer",
+            transactions: recipients,
+            signatures: recipients,
+            rent: 0,
+            refundable_rent: 0,
+        },
+        close_proof_accounts(&proof_accounts, recipients),
+    ])
+}
+
+/// Rent of the launch authority's token account once it is reallocated for
+/// confidential transfers.
+fn token_account_rent(rpc: &RpcClient, transfer_fee: bool) -> Result<u64> {
+    let mut extensions = vec![
+        ExtensionType::ImmutableOwner,
+        ExtensionType::ConfidentialTransferAccount,
+    ];
+    if transfer_fee {
+        extensions.push(ExtensionType::TransferFeeAmount);
+        extensions.push(ExtensionType::ConfidentialTransferFeeAmount);
+    }
+    let space = ExtensionType::try_calculate_account_len::<Account>(&extensions)?;
+    Ok(rpc.get_minimum_balance_for_rent_exemption(space)?)
+}
+
+/// Creating and verifying a set of proof accounts, `times` over. The refundable rent is
+/// that of one set, since each set is closed before the next is created.
+fn create_proof_accounts(
+    rpc: &RpcClient,
+    accounts: &[ProofAccount],
+    times: u64,
+) -> Result<CostLine> {
+    let mut transactions = 0;
+    let mut signatures = 0;
+    let mut refundable_rent = 0;
+    for account in accounts {
+        // The new account signs its creation alongside the payer
+        transactions += 1;
+        signatures += 2;
+        if account.split {
+            transactions += 1;
+            signatures += 1;
+        }
+        refundable_rent += rpc.get_minimum_balance_for_rent_exemption(account.space)?;
+    }
+
+    Ok(CostLine {
+        step: "create_proof_accounts",
+        transactions: transactions * times,
+        signatures: signatures * times,
+        rent: 0,
+        refundable_rent,
+    })
+}
+
+/// Closing a set of proof accounts, one transaction each, `times` over.
+fn close_proof_accounts(accounts: &[ProofAccount], times: u64) -> CostLine {
+    let closes = accounts.len() as u64 * times;
+    CostLine {
+        step: "close_proof_accounts",
+        transactions: closes,
+        signatures: closes,
+        rent: 0,
+        refundable_rent: 0,
+    }
+}
+
+fn single_transaction(step: &'static str) -> CostLine {
+    CostLine {
+        step,
+        transactions: 1,
+        signatures: 1,
+        rent: 0,
+        refundable_rent: 0,
+    }
+}
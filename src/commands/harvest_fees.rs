@@ -0,0 +1,277 @@
+use crate::cli::HarvestFeesArgs;
+use crate::compute_budget;
+use crate::config::{create_rpc_client, expand_path, load_keypair};
+use crate::crypto::ConfidentialKeys;
+use anyhow::{anyhow, Context, Result};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::ConfidentialTransferAccount,
+        confidential_transfer_fee::{
+            account_info::WithheldTokensInfo,
+            instruction::{harvest_withheld_tokens_to_mint, withdraw_withheld_tokens_from_mint},
+            ConfidentialTransferFeeAmount, ConfidentialTransferFeeConfig,
+            EncryptedWithheldAmount,
+        },
+        transfer_fee::TransferFeeConfig,
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    solana_zk_sdk::encryption::{
+        auth_encryption::AeCiphertext,
+        elgamal::ElGamalCiphertext,
+        pod::elgamal::PodElGamalPubkey,
+    },
+    state::{Account, Mint},
+};
+use spl_token_client::{
+    client::{ProgramRpcClient, ProgramRpcClientSendTransaction},
+    token::Token,
+};
+use spl_token_confidential_transfer_proof_extraction::instruction::ProofLocation;
+use std::sync::Arc;
+
+/// Each harvest source is one writable account; this keeps transactions under the size limit.
+const HARVEST_SOURCES_PER_TRANSACTION: usize = 20;
+
+pub async fn execute(args: HarvestFeesArgs) -> Result<()> {
+    let keypair = load_keypair(&expand_path(&args.keypair))?;
+    let authority_keys = ConfidentialKeys::derive_from_keypair(&keypair)?;
+    let rpc = create_rpc_client(&args.rpc);
+
+    let ata = get_associated_token_address_with_program_id(
+        &keypair.pubkey(),
+        &args.mint,
+        &spl_token_2022::id(),
+    );
+
+    status!("Harvesting confidential transfer fees...");
+    status!("  Mint: {}", args.mint);
+    status!("  Destination: {}", ata);
+
+    let mint_account_data = rpc.get_account(&args.mint)?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_account_data.data)?;
+    let fee_config = mint_state
+        .get_extension::<TransferFeeConfig>()
+        .map_err(|_| anyhow!("Mint {} has no transfer fee", args.mint))?;
+    let ct_fee_config = mint_state
+        .get_extension::<ConfidentialTransferFeeConfig>()
+        .map_err(|_| anyhow!("Mint {} has no confidential transfer fee config", args.mint))?;
+
+    if Option::<Pubkey>::from(fee_config.withdraw_withheld_authority) != Some(keypair.pubkey()) {
+        return Err(anyhow!(
+            "{} is not the withdraw withheld authority for mint {}",
+            keypair.pubkey(),
+            args.mint
+        ));
+    }
+    if ct_fee_config.withdraw_withheld_authority_elgamal_pubkey
+        != PodElGamalPubkey::from(authority_keys.elgamal_pubkey())
+    {
+        return Err(anyhow!(
+            "Withheld fees on mint {} are not encrypted to this keypair's ElGamal key",
+            args.mint
+        ));
+    }
+    let decimals = mint_state.base.decimals;
+
+    // Withheld fees sit on recipient token accounts until harvested into the mint
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            0,
+            &args.mint.to_bytes(),
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let accounts = rpc
+        .get_program_accounts_with_config(&spl_token_2022::id(), config)
+        .context("Failed to query token accounts")?;
+
+    let mut sources = Vec::new();
+    let mut account_withheld = 0u64;
+    let mut undecryptable = 0;
+    for (pubkey, account) in &accounts {
+        let Ok(state) = StateWithExtensions::<Account>::unpack(&account.data) else {
+            continue;
+        };
+        let Ok(fee_amount) = state.get_extension::<ConfidentialTransferFeeAmount>() else {
+            continue;
+        };
+        // Harvesting does not need the amount, so an account that cannot be decrypted is
+        // still harvested
+        match decrypt_withheld(&fee_amount.withheld_amount, &authority_keys) {
+            Some(0) => {}
+            Some(withheld) => {
+                sources.push(*pubkey);
+                account_withheld += withheld;
+            }
+            None => {
+                status!("  {}: could not decrypt withheld fees, harvesting anyway", pubkey);
+                sources.push(*pubkey);
+                undecryptable += 1;
+            }
+        }
+    }
+
+    if sources.is_empty() {
+        status!("No withheld fees on token accounts");
+    } else {
+        status!(
+            "Harvesting {} from {} token accounts into the mint...",
+            account_withheld,
+            sources.len()
+        );
+        if undecryptable > 0 {
+            status!("  (plus an unknown amount from {} accounts)", undecryptable);
+        }
+
+        for batch in sources.chunks(HARVEST_SOURCES_PER_TRANSACTION) {
+            let batch_refs: Vec<&Pubkey> = batch.iter().collect();
+            let harvest_ix =
+                harvest_withheld_tokens_to_mint(&spl_token_2022::id(), &args.mint, &batch_refs)?;
+
+            let tx = compute_budget::signed_transaction(
+                &rpc,
+                &[harvest_ix],
+                &keypair.pubkey(),
+                &[&keypair],
+            )?;
+
+            let signature = rpc
+                .send_and_confirm_transaction_with_spinner(&tx)
+                .context("Failed to harvest withheld fees to mint")?;
+            status!("  Signature: {}", signature);
+        }
+    }
+
+    // Re-read the mint for the harvested total
+    let mint_account_data = rpc.get_account(&args.mint)?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_account_data.data)?;
+    let ct_fee_config = mint_state.get_extension::<ConfidentialTransferFeeConfig>()?;
+    // Withdrawing proves the amount, so it stops here if the total cannot be decrypted
+    let mint_withheld = decrypt_withheld(&ct_fee_config.withheld_amount, &authority_keys)
+        .ok_or_else(|| {
+            anyhow!("Fees were harvested into the mint, but its withheld amount could not be decrypted, so it cannot be withdrawn")
+        })?;
+
+    if mint_withheld == 0 {
+        status!("\nNo withheld fees to withdraw.");
+        return Ok(());
+    }
+
+    status!("Withdrawing {} withheld fees to {}...", mint_withheld, ata);
+
+    let account_data = rpc
+        .get_account(&ata)
+        .with_context(|| format!("Token account not found for wallet {}", keypair.pubkey()))?;
+    let account_state = StateWithExtensions::<Account>::unpack(&account_data.data)
+        .context("Failed to unpack token account")?;
+    let ct_account = account_state
+        .get_extension::<ConfidentialTransferAccount>()
+        .context("Confidential transfer extension not found on account - run configure first")?;
+
+    // The withdrawn fees land directly in the available balance
+    let current_decryptable: AeCiphertext = ct_account.decryptable_available_balance.try_into()
+        .map_err(|_| anyhow!("Invalid decryptable balance"))?;
+    let current_balance = authority_keys
+        .aes_key
+        .decrypt(&current_decryptable)
+        .ok_or_else(|| anyhow!("Failed to decrypt available balance"))?;
+    let new_decryptable_balance = authority_keys.aes_key.encrypt(current_balance + mint_withheld);
+
+    status!("  Generating proof...");
+    let proof_data = WithheldTokensInfo::new(&ct_fee_config.withheld_amount)
+        .generate_proof_data(&authority_keys.elgamal_keypair, &authority_keys.elgamal_pubkey())
+        .map_err(|e| anyhow!("Failed to generate withdraw withheld proof: {}", e))?;
+
+    let rpc_client = Arc::new(NonblockingRpcClient::new_with_commitment(
+        args.rpc.clone(),
+        CommitmentConfig::confirmed(),
+    ));
+
+    let program_client = Arc::new(ProgramRpcClient::new(
+        rpc_client.clone(),
+        ProgramRpcClientSendTransaction,
+    ));
+
+    let token = compute_budget::budgeted_token(
+        Token::new(
+            program_client,
+            &spl_token_2022::id(),
+            &args.mint,
+            Some(decimals),
+            Arc::new(keypair.insecure_clone()),
+        ),
+        &rpc_client,
+        &[ata],
+    )
+    .await?;
+
+    let equality_proof_keypair = Keypair::new();
+
+    status!("  Creating proof context account...");
+    token.confidential_transfer_create_context_state_account(
+        &equality_proof_keypair.pubkey(),
+        &keypair.pubkey(),
+        &proof_data,
+        false,
+        &[&keypair, &equality_proof_keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to create equality proof account: {}", e))?;
+
+    let withdraw_ixs = withdraw_withheld_tokens_from_mint(
+        &spl_token_2022::id(),
+        &args.mint,
+        &ata,
+        &new_decryptable_balance.into(),
+        &keypair.pubkey(),
+        &[],
+        ProofLocation::ContextStateAccount(&equality_proof_keypair.pubkey()),
+    )?;
+
+    let tx = compute_budget::signed_transaction(
+        &rpc,
+        &withdraw_ixs,
+        &keypair.pubkey(),
+        &[&keypair],
+    )?;
+
+    let signature = rpc.send_and_confirm_transaction_with_spinner(&tx)
+        .context("Failed to withdraw withheld fees from mint")?;
+
+    // Close the context state account to recover rent
+    status!("  Cleaning up proof account...");
+    token.confidential_transfer_close_context_state_account(
+        &equality_proof_keypair.pubkey(),
+        &ata,
+        &keypair.pubkey(),
+        &[&keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to close proof account: {}", e))?;
+
+    status!("\nHarvest complete.");
+    status!("Signature: {}", signature);
+    status!("Withdrawn fees: {}", mint_withheld);
+    status!("Available balance: {}", current_balance + mint_withheld);
+
+    Ok(())
+}
+
+/// Decrypts a withheld fee ciphertext with the withdraw withheld authority's ElGamal key.
+fn decrypt_withheld(withheld: &EncryptedWithheldAmount, keys: &ConfidentialKeys) -> Option<u64> {
+    let ciphertext: ElGamalCiphertext = (*withheld).try_into().ok()?;
+    ciphertext.decrypt_u32(keys.elgamal_keypair.secret())
+}
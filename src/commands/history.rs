@@ -0,0 +1,656 @@
+use crate::cli::HistoryArgs;
+use crate::commands::audit::{
+    decode_confidential_transfer_instruction, decrypt_split_amount, fetch_signatures,
+    fetch_transaction, FetchedTransaction,
+};
+use crate::config::{create_rpc_client, expand_path, load_keypair};
+use crate::crypto::ConfidentialKeys;
+use anyhow::Result;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    message::compiled_instruction::CompiledInstruction, pubkey::Pubkey, signature::Signer,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::{
+    extension::{
+        confidential_mint_burn::instruction::{
+            BurnInstructionData, ConfidentialMintBurnInstruction, MintInstructionData,
+        },
+        confidential_transfer::{
+            instruction::{
+                ApplyPendingBalanceData, ConfidentialTransferInstruction, DepositInstructionData,
+                TransferInstructionData, TransferWithFeeInstructionData, WithdrawInstructionData,
+            },
+            DecryptableBalance,
+        },
+        confidential_transfer_fee::instruction::ConfidentialTransferFeeInstruction,
+    },
+    instruction::{decode_instruction_data, decode_instruction_type, TokenInstruction},
+    solana_zk_sdk::{
+        encryption::auth_encryption::AeCiphertext,
+        zk_elgamal_proof_program::{
+            self,
+            instruction::ProofInstruction,
+            proof_data::{
+                BatchedGroupedCiphertext2HandlesValidityProofContext,
+                BatchedGroupedCiphertext2HandlesValidityProofData,
+                BatchedGroupedCiphertext3HandlesValidityProofContext,
+                BatchedGroupedCiphertext3HandlesValidityProofData,
+            },
+        },
+    },
+};
+
+/// Handle positions in the transfer amount ciphertext: source, destination, auditor.
+const SOURCE_HANDLE: usize = 0;
+const DESTINATION_HANDLE: usize = 1;
+/// Handle position of the destination in the fee ciphertext (the other is the withdraw
+/// withheld authority).
+const FEE_DESTINATION_HANDLE: usize = 0;
+/// Handle position of the token account in a confidential mint or burn amount ciphertext
+/// (the others are the supply and the auditor).
+const MINT_BURN_ACCOUNT_HANDLE: usize = 0;
+/// Token account, mint, then either the instructions sysvar or the equality proof context
+/// account, so the validity proof context account is always fourth.
+const MINT_BURN_VALIDITY_CONTEXT_ACCOUNT: usize = 3;
+
+/// Running view of the confidential balance. Either part becomes `None` once an amount
+/// could not be decrypted and is known again at the next snapshot the owner can decrypt.
+struct RunningBalance {
+    available: Option<u64>,
+    pending: Option<u64>,
+}
+
+impl RunningBalance {
+    fn total(&self) -> Option<u64> {
+        Some(self.available? + self.pending?)
+    }
+
+    fn credit_pending(&mut self, amount: Option<u64>) {
+        self.pending = match (self.pending, amount) {
+            (Some(pending), Some(amount)) => Some(pending + amount),
+            _ => None,
+        };
+    }
+}
+
+/// One instruction of the statement, with the signed change to the confidential balance.
+struct StatementLine {
+    kind: &'static str,
+    counterparty: Option<Pubkey>,
+    change: Option<i128>,
+}
+
+pub async fn execute(args: HistoryArgs) -> Result<()> {
+    let keypair = load_keypair(&expand_path(&args.keypair))?;
+    let owner_keys = ConfidentialKeys::derive_from_keypair(&keypair)?;
+    let rpc = create_rpc_client(&args.rpc);
+
+    let ata = get_associated_token_address_with_program_id(
+        &keypair.pubkey(),
+        &args.mint,
+        &spl_token_2022::id(),
+    );
+
+    status!("Confidential transfer history...");
+    status!("  Mint: {}", args.mint);
+    status!("  Token Account: {}", ata);
+
+    let signatures = fetch_signatures(&rpc, &ata)?;
+    status!("  Transactions: {}\n", signatures.len());
+
+    status!(
+        "  {:>10}  {:<14} {:>20} {:>20}  {:<44}  {}",
+        "Slot", "Type", "Change", "Balance", "Counterparty", "Signature"
+    );
+
+    // Balances are unknown until the account's configuration (zero balance) is seen
+    let mut balance = RunningBalance {
+        available: None,
+        pending: None,
+    };
+
+    for signature in &signatures {
+        let fetched = fetch_transaction(&rpc, signature)?;
+
+        let lines = statement_lines(&rpc, &fetched, &ata, &owner_keys, &mut balance);
+        for line in lines {
+            status!(
+                "  {:>10}  {:<14} {:>20} {:>20}  {:<44}  {}",
+                fetched.slot,
+                line.kind,
+                line.change
+                    .map_or_else(|| "?".to_string(), |c| format!("{:+}", c)),
+                balance
+                    .total()
+                    .map_or_else(|| "?".to_string(), |b| b.to_string()),
+                line.counterparty
+                    .map_or_else(String::new, |c| c.to_string()),
+                signature
+            );
+        }
+    }
+
+    status!();
+    match (balance.available, balance.pending) {
+        (Some(available), Some(pending)) => {
+            status!("Available: {}", available);
+            status!("Pending: {}", pending);
+        }
+        _ => status!("Some amounts could not be decrypted; run `balance` for the current balance"),
+    }
+
+    Ok(())
+}
+
+/// Confidential token-2022 instructions the statement understands.
+enum ConfidentialInstruction<'a> {
+    Transfer(ConfidentialTransferInstruction, &'a [u8]),
+    MintBurn(ConfidentialMintBurnInstruction, &'a [u8]),
+}
+
+impl<'a> ConfidentialInstruction<'a> {
+    fn decode(data: &'a [u8]) -> Option<Self> {
+        if let Some((instruction_type, extension_data)) =
+            decode_confidential_transfer_instruction(data)
+        {
+            return Some(Self::Transfer(instruction_type, extension_data));
+        }
+
+        let (&tag, extension_data) = data.split_first()?;
+        if !matches!(
+            TokenInstruction::unpack(&[tag]),
+            Ok(TokenInstruction::ConfidentialMintBurnExtension)
+        ) {
+            return None;
+        }
+        let instruction_type = decode_instruction_type(extension_data).ok()?;
+        Some(Self::MintBurn(instruction_type, extension_data))
+    }
+}
+
+/// Applies every confidential instruction in the transaction that touches `ata` to the
+/// running balance and describes it.
+fn statement_lines(
+    rpc: &RpcClient,
+    fetched: &FetchedTransaction,
+    ata: &Pubkey,
+    owner_keys: &ConfidentialKeys,
+    balance: &mut RunningBalance,
+) -> Vec<StatementLine> {
+    let mut lines = Vec::new();
+
+    for (index, ix) in fetched
+        .transaction
+        .message
+        .instructions()
+        .iter()
+        .enumerate()
+    {
+        // Skipped rather than guessed when a lookup table address could not be resolved
+        let Some(accounts) = fetched.instruction_accounts(ix) else {
+            continue;
+        };
+        let Some(first_account) = accounts.first() else {
+            continue;
+        };
+        let is_own_account = first_account == ata;
+        let lines_before = lines.len();
+
+        let decoded = if fetched.program_id(ix) == Some(&spl_token_2022::id()) {
+            ConfidentialInstruction::decode(&ix.data)
+        } else {
+            None
+        };
+
+        match decoded {
+            Some(ConfidentialInstruction::Transfer(
+                ConfidentialTransferInstruction::ConfigureAccount
+                | ConfidentialTransferInstruction::ConfigureAccountWithRegistry,
+                _,
+            )) if is_own_account => {
+                balance.available = Some(0);
+                balance.pending = Some(0);
+                lines.push(StatementLine {
+                    kind: "configure",
+                    counterparty: None,
+                    change: Some(0),
+                });
+            }
+            Some(ConfidentialInstruction::Transfer(
+                ConfidentialTransferInstruction::Deposit,
+                extension_data,
+            )) if is_own_account => {
+                let Ok(data) = decode_instruction_data::<DepositInstructionData>(extension_data)
+                else {
+                    continue;
+                };
+                let amount = u64::from(data.amount);
+                balance.credit_pending(Some(amount));
+                lines.push(StatementLine {
+                    kind: "deposit",
+                    counterparty: None,
+                    change: Some(amount.into()),
+                });
+            }
+            Some(ConfidentialInstruction::Transfer(
+                ConfidentialTransferInstruction::Withdraw,
+                extension_data,
+            )) if is_own_account => {
+                let Ok(data) = decode_instruction_data::<WithdrawInstructionData>(extension_data)
+                else {
+                    continue;
+                };
+                let amount = u64::from(data.amount);
+                balance.available =
+                    decrypt_decryptable(&data.new_decryptable_available_balance, owner_keys);
+                lines.push(StatementLine {
+                    kind: "withdraw",
+                    counterparty: None,
+                    change: Some(-i128::from(amount)),
+                });
+            }
+            Some(ConfidentialInstruction::Transfer(
+                ConfidentialTransferInstruction::ApplyPendingBalance,
+                extension_data,
+            )) if is_own_account => {
+                let Ok(data) = decode_instruction_data::<ApplyPendingBalanceData>(extension_data)
+                else {
+                    continue;
+                };
+                // Moves pending into available; the total is unchanged but fully known again
+                balance.available =
+                    decrypt_decryptable(&data.new_decryptable_available_balance, owner_keys);
+                balance.pending = Some(0);
+                lines.push(StatementLine {
+                    kind: "apply_pending",
+                    counterparty: None,
+                    change: Some(0),
+                });
+            }
+            Some(ConfidentialInstruction::Transfer(
+                instruction_type @ (ConfidentialTransferInstruction::Transfer
+                | ConfidentialTransferInstruction::TransferWithFee),
+                extension_data,
+            )) => {
+                if accounts.len() < 3 {
+                    continue;
+                }
+                let (source, destination) = (accounts[0], accounts[2]);
+
+                let Some(proofs) = TransferProofs::decode(instruction_type, extension_data) else {
+                    continue;
+                };
+
+                if source == *ata {
+                    let new_available =
+                        decrypt_decryptable(&proofs.new_source_decryptable_balance, owner_keys);
+                    // The drop in available balance is exact; fall back to the source handle
+                    let amount = match (balance.available, new_available) {
+                        (Some(before), Some(after)) => before.checked_sub(after),
+                        _ => None,
+                    }
+                    .or_else(|| {
+                        proofs.transfer_amount(
+                            rpc,
+                            fetched,
+                            index,
+                            &accounts,
+                            SOURCE_HANDLE,
+                            owner_keys,
+                        )
+                    });
+                    balance.available = new_available;
+                    lines.push(StatementLine {
+                        kind: "transfer_out",
+                        counterparty: Some(destination),
+                        change: amount.map(|a| -i128::from(a)),
+                    });
+                }
+
+                if destination == *ata {
+                    let gross = proofs.transfer_amount(
+                        rpc,
+                        fetched,
+                        index,
+                        &accounts,
+                        DESTINATION_HANDLE,
+                        owner_keys,
+                    );
+                    // With a fee, the recipient is credited the amount minus the withheld fee
+                    let received = if proofs.with_fee {
+                        gross
+                            .zip(proofs.fee_amount(rpc, fetched, index, &accounts, owner_keys))
+                            .and_then(|(gross, fee)| gross.checked_sub(fee))
+                    } else {
+                        gross
+                    };
+                    balance.credit_pending(received);
+                    lines.push(StatementLine {
+                        kind: "transfer_in",
+                        counterparty: Some(source),
+                        change: received.map(i128::from),
+                    });
+                }
+            }
+            Some(ConfidentialInstruction::MintBurn(
+                ConfidentialMintBurnInstruction::Mint,
+                extension_data,
+            )) if is_own_account => {
+                let Ok(data) = decode_instruction_data::<MintInstructionData>(extension_data)
+                else {
+                    continue;
+                };
+                let amount = validity_proof_amount(
+                    rpc,
+                    fetched,
+                    index,
+                    data.ciphertext_validity_proof_instruction_offset,
+                    accounts.get(MINT_BURN_VALIDITY_CONTEXT_ACCOUNT),
+                    MINT_BURN_ACCOUNT_HANDLE,
+                    owner_keys,
+                );
+                balance.credit_pending(amount);
+                lines.push(StatementLine {
+                    kind: "mint",
+                    counterparty: None,
+                    change: amount.map(i128::from),
+                });
+            }
+            Some(ConfidentialInstruction::MintBurn(
+                ConfidentialMintBurnInstruction::Burn,
+                extension_data,
+            )) if is_own_account => {
+                let Ok(data) = decode_instruction_data::<BurnInstructionData>(extension_data)
+                else {
+                    continue;
+                };
+                let new_available =
+                    decrypt_decryptable(&data.new_decryptable_available_balance, owner_keys);
+                let amount = match (balance.available, new_available) {
+                    (Some(before), Some(after)) => before.checked_sub(after),
+                    _ => None,
+                }
+                .or_else(|| {
+                    validity_proof_amount(
+                        rpc,
+                        fetched,
+                        index,
+                        data.ciphertext_validity_proof_instruction_offset,
+                        accounts.get(MINT_BURN_VALIDITY_CONTEXT_ACCOUNT),
+                        MINT_BURN_ACCOUNT_HANDLE,
+                        owner_keys,
+                    )
+                });
+                balance.available = new_available;
+                lines.push(StatementLine {
+                    kind: "burn",
+                    counterparty: None,
+                    change: amount.map(|a| -i128::from(a)),
+                });
+            }
+            _ => {}
+        }
+
+        // Anything else that writes the account, such as a withheld fee withdrawal or a
+        // CPI, moves an amount the running balance cannot follow
+        if lines.len() == lines_before
+            && writes_account(fetched, ix, ata)
+            && !leaves_confidential_balance(fetched.program_id(ix), &ix.data)
+        {
+            balance.available = None;
+            balance.pending = None;
+            lines.push(StatementLine {
+                kind: "other",
+                counterparty: None,
+                change: None,
+            });
+        }
+    }
+
+    lines
+}
+
+/// Whether `ix` references `account` in a writable position.
+fn writes_account(fetched: &FetchedTransaction, ix: &CompiledInstruction, account: &Pubkey) -> bool {
+    ix.accounts.iter().any(|i| {
+        fetched.account_keys.get(*i as usize) == Some(account)
+            && fetched.transaction.message.is_maybe_writable(*i as usize, None)
+    })
+}
+
+/// Instructions that write a token account without touching its confidential balance:
+/// associated account creation, public token instructions and the confidential transfer
+/// instructions that only change account settings.
+fn leaves_confidential_balance(program_id: Option<&Pubkey>, data: &[u8]) -> bool {
+    if program_id == Some(&spl_associated_token_account::id()) {
+        return true;
+    }
+    if program_id != Some(&spl_token_2022::id()) {
+        return false;
+    }
+
+    let extension_data = data.get(1..).unwrap_or_default();
+    match TokenInstruction::unpack(data) {
+        Ok(TokenInstruction::ConfidentialTransferExtension) => matches!(
+            decode_instruction_type(extension_data),
+            Ok(ConfidentialTransferInstruction::ApproveAccount
+                | ConfidentialTransferInstruction::EmptyAccount
+                | ConfidentialTransferInstruction::EnableConfidentialCredits
+                | ConfidentialTransferInstruction::DisableConfidentialCredits
+                | ConfidentialTransferInstruction::EnableNonConfidentialCredits
+                | ConfidentialTransferInstruction::DisableNonConfidentialCredits)
+        ),
+        Ok(TokenInstruction::ConfidentialTransferFeeExtension) => !matches!(
+            decode_instruction_type(extension_data),
+            Ok(ConfidentialTransferFeeInstruction::WithdrawWithheldTokensFromMint
+                | ConfidentialTransferFeeInstruction::WithdrawWithheldTokensFromAccounts)
+        ),
+        Ok(TokenInstruction::ConfidentialMintBurnExtension) => false,
+        Ok(_) => true,
+        Err(_) => false,
+    }
+}
+
+/// The parts of a `Transfer`/`TransferWithFee` instruction needed to recover its amount.
+struct TransferProofs {
+    with_fee: bool,
+    new_source_decryptable_balance: DecryptableBalance,
+    validity_proof_offset: i8,
+    fee_validity_proof_offset: i8,
+    /// Whether any proof is in the same transaction, which inserts the instructions sysvar
+    /// ahead of the context state accounts
+    uses_instructions_sysvar: bool,
+}
+
+impl TransferProofs {
+    fn decode(
+        instruction_type: ConfidentialTransferInstruction,
+        extension_data: &[u8],
+    ) -> Option<Self> {
+        match instruction_type {
+            ConfidentialTransferInstruction::Transfer => {
+                let data =
+                    decode_instruction_data::<TransferInstructionData>(extension_data).ok()?;
+                Some(Self {
+                    with_fee: false,
+                    new_source_decryptable_balance: data.new_source_decryptable_available_balance,
+                    validity_proof_offset: data.ciphertext_validity_proof_instruction_offset,
+                    fee_validity_proof_offset: 0,
+                    uses_instructions_sysvar: data.equality_proof_instruction_offset != 0
+                        || data.ciphertext_validity_proof_instruction_offset != 0
+                        || data.range_proof_instruction_offset != 0,
+                })
+            }
+            ConfidentialTransferInstruction::TransferWithFee => {
+                let data =
+                    decode_instruction_data::<TransferWithFeeInstructionData>(extension_data)
+                        .ok()?;
+                Some(Self {
+                    with_fee: true,
+                    new_source_decryptable_balance: data.new_source_decryptable_available_balance,
+                    validity_proof_offset: data
+                        .transfer_amount_ciphertext_validity_proof_instruction_offset,
+                    fee_validity_proof_offset: data
+                        .fee_ciphertext_validity_proof_instruction_offset,
+                    uses_instructions_sysvar: data.equality_proof_instruction_offset != 0
+                        || data.transfer_amount_ciphertext_validity_proof_instruction_offset != 0
+                        || data.fee_sigma_proof_instruction_offset != 0
+                        || data.fee_ciphertext_validity_proof_instruction_offset != 0
+                        || data.range_proof_instruction_offset != 0,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Decrypts the transfer amount from the ciphertext validity proof using the handle
+    /// at `handle` (source or destination).
+    fn transfer_amount(
+        &self,
+        rpc: &RpcClient,
+        fetched: &FetchedTransaction,
+        index: usize,
+        accounts: &[Pubkey],
+        handle: usize,
+        owner_keys: &ConfidentialKeys,
+    ) -> Option<u64> {
+        // Source, mint, destination, [sysvar], equality, validity, ...
+        let context_account_index = 4 + usize::from(self.uses_instructions_sysvar);
+        validity_proof_amount(
+            rpc,
+            fetched,
+            index,
+            self.validity_proof_offset,
+            accounts.get(context_account_index),
+            handle,
+            owner_keys,
+        )
+    }
+
+    /// Decrypts the withheld fee from the fee ciphertext validity proof (destination handle).
+    fn fee_amount(
+        &self,
+        rpc: &RpcClient,
+        fetched: &FetchedTransaction,
+        index: usize,
+        accounts: &[Pubkey],
+        owner_keys: &ConfidentialKeys,
+    ) -> Option<u64> {
+        // ..., validity, fee sigma, fee validity, range
+        let context_account_index = 6 + usize::from(self.uses_instructions_sysvar);
+        let data = find_proof_instruction(
+            rpc,
+            fetched,
+            index,
+            self.fee_validity_proof_offset,
+            accounts.get(context_account_index),
+            ProofInstruction::VerifyBatchedGroupedCiphertext2HandlesValidity,
+        )?;
+        let proof = ProofInstruction::proof_data::<
+            BatchedGroupedCiphertext2HandlesValidityProofData,
+            BatchedGroupedCiphertext2HandlesValidityProofContext,
+        >(&data)?;
+
+        let lo = proof
+            .context
+            .grouped_ciphertext_lo
+            .try_extract_ciphertext(FEE_DESTINATION_HANDLE)
+            .ok()?;
+        let hi = proof
+            .context
+            .grouped_ciphertext_hi
+            .try_extract_ciphertext(FEE_DESTINATION_HANDLE)
+            .ok()?;
+        decrypt_split_amount(&lo, &hi, owner_keys.elgamal_keypair.secret())
+    }
+}
+
+/// Decrypts the amount in a 3-handle ciphertext validity proof using the handle at
+/// `handle`.
+fn validity_proof_amount(
+    rpc: &RpcClient,
+    fetched: &FetchedTransaction,
+    index: usize,
+    offset: i8,
+    context_account: Option<&Pubkey>,
+    handle: usize,
+    owner_keys: &ConfidentialKeys,
+) -> Option<u64> {
+    let data = find_proof_instruction(
+        rpc,
+        fetched,
+        index,
+        offset,
+        context_account,
+        ProofInstruction::VerifyBatchedGroupedCiphertext3HandlesValidity,
+    )?;
+    let proof = ProofInstruction::proof_data::<
+        BatchedGroupedCiphertext3HandlesValidityProofData,
+        BatchedGroupedCiphertext3HandlesValidityProofContext,
+    >(&data)?;
+
+    let lo = proof
+        .context
+        .grouped_ciphertext_lo
+        .try_extract_ciphertext(handle)
+        .ok()?;
+    let hi = proof
+        .context
+        .grouped_ciphertext_hi
+        .try_extract_ciphertext(handle)
+        .ok()?;
+    decrypt_split_amount(&lo, &hi, owner_keys.elgamal_keypair.secret())
+}
+
+/// Finds the data of the proof verification instruction behind a transfer proof: either
+/// an instruction in the same transaction (non-zero `offset`) or the instruction that
+/// verified the proof into `context_account`, found through that account's history.
+fn find_proof_instruction(
+    rpc: &RpcClient,
+    fetched: &FetchedTransaction,
+    index: usize,
+    offset: i8,
+    context_account: Option<&Pubkey>,
+    proof_type: ProofInstruction,
+) -> Option<Vec<u8>> {
+    if offset != 0 {
+        let proof_index = usize::try_from(index as i64 + i64::from(offset)).ok()?;
+        let ix = fetched
+            .transaction
+            .message
+            .instructions()
+            .get(proof_index)?;
+        let is_proof = fetched.program_id(ix) == Some(&zk_elgamal_proof_program::id())
+            && ProofInstruction::instruction_type(&ix.data) == Some(proof_type);
+        return is_proof.then(|| ix.data.clone());
+    }
+
+    let context_account = context_account?;
+    let signatures = fetch_signatures(rpc, context_account).ok()?;
+    for signature in signatures {
+        let Ok(proof_tx) = fetch_transaction(rpc, &signature) else {
+            continue;
+        };
+        for ix in proof_tx.transaction.message.instructions() {
+            let writes_context = ix
+                .accounts
+                .first()
+                .and_then(|i| proof_tx.account_keys.get(*i as usize))
+                == Some(context_account);
+            if proof_tx.program_id(ix) == Some(&zk_elgamal_proof_program::id())
+                && ProofInstruction::instruction_type(&ix.data) == Some(proof_type)
+                && writes_context
+            {
+                return Some(ix.data.clone());
+            }
+        }
+    }
+
+    None
+}
+
+fn decrypt_decryptable(balance: &DecryptableBalance, owner_keys: &ConfidentialKeys) -> Option<u64> {
+    let ciphertext: AeCiphertext = (*balance).try_into().ok()?;
+    owner_keys.aes_key.decrypt(&ciphertext)
+}
@@ -0,0 +1,142 @@
+use crate::cli::MintConfigArgs;
+use crate::compute_budget;
+use crate::config::{create_rpc_client, expand_path, load_keypair};
+use crate::crypto::{format_elgamal_pubkey, parse_elgamal_pubkey};
+use crate::output;
+use anyhow::{anyhow, Context, Result};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::Signer,
+};
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::{instruction::update_mint, ConfidentialTransferMint},
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    instruction::{set_authority, AuthorityType},
+    solana_zk_sdk::encryption::{elgamal::ElGamalPubkey, pod::elgamal::PodElGamalPubkey},
+    state::Mint,
+};
+
+pub async fn execute(args: MintConfigArgs) -> Result<()> {
+    let rpc = create_rpc_client(&args.rpc);
+
+    let mint_account_data = rpc.get_account(&args.mint)?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_account_data.data)?;
+    let ct_mint = mint_state
+        .get_extension::<ConfidentialTransferMint>()
+        .map_err(|_| anyhow!("Mint {} does not have confidential transfers enabled", args.mint))?;
+
+    let authority = Option::<Pubkey>::from(ct_mint.authority);
+    let auto_approve = bool::from(ct_mint.auto_approve_new_accounts);
+    let auditor = Option::<PodElGamalPubkey>::from(ct_mint.auditor_elgamal_pubkey)
+        .map(|p| ElGamalPubkey::try_from(p).map_err(|_| anyhow!("Invalid auditor ElGamal pubkey")))
+        .transpose()?;
+
+    status!("Confidential transfer config for {}", args.mint);
+    status!("  Authority: {}", display_authority(authority));
+    status!("  Auditor: {}", display_auditor(auditor.as_ref()));
+    status!("  Auto-approve new accounts: {}", auto_approve);
+
+    let new_auditor = if args.remove_auditor {
+        None
+    } else if let Some(s) = &args.auditor {
+        Some(parse_elgamal_pubkey(s)?)
+    } else {
+        auditor
+    };
+    let new_auto_approve = args.auto_approve.unwrap_or(auto_approve);
+
+    let auditor_changed = new_auditor != auditor;
+    let auto_approve_changed = new_auto_approve != auto_approve;
+    let authority_changed = args.new_authority.is_some_and(|a| Some(a) != authority);
+
+    if !auditor_changed && !auto_approve_changed && !authority_changed {
+        if args.auditor.is_some()
+            || args.remove_auditor
+            || args.auto_approve.is_some()
+            || args.new_authority.is_some()
+        {
+            status!("\nNo changes - the mint already has this configuration.");
+        }
+        return Ok(());
+    }
+
+    let keypair = load_keypair(&expand_path(&args.keypair))?;
+    if authority != Some(keypair.pubkey()) {
+        return Err(anyhow!(
+            "{} is not the confidential transfer authority for mint {}",
+            keypair.pubkey(),
+            args.mint
+        ));
+    }
+
+    status!("\nChanges:");
+    if auditor_changed {
+        status!(
+            "  Auditor: {} -> {}",
+            display_auditor(auditor.as_ref()),
+            display_auditor(new_auditor.as_ref())
+        );
+    }
+    if auto_approve_changed {
+        status!("  Auto-approve new accounts: {} -> {}", auto_approve, new_auto_approve);
+    }
+    if let (true, Some(new_authority)) = (authority_changed, args.new_authority) {
+        status!("  Authority: {} -> {}", display_authority(authority), new_authority);
+    }
+
+    if !args.yes && !output::confirm("Apply these changes?")? {
+        status!("Aborted.");
+        return Ok(());
+    }
+
+    let mut instructions = Vec::new();
+
+    // update_mint always writes both settings, so unchanged values are passed through
+    if auditor_changed || auto_approve_changed {
+        instructions.push(update_mint(
+            &spl_token_2022::id(),
+            &args.mint,
+            &keypair.pubkey(),
+            &[],
+            new_auto_approve,
+            new_auditor.map(PodElGamalPubkey::from),
+        )?);
+    }
+
+    // Rotate the authority last so the update above is still signed by the current one
+    if let (true, Some(new_authority)) = (authority_changed, args.new_authority) {
+        instructions.push(set_authority(
+            &spl_token_2022::id(),
+            &args.mint,
+            Some(&new_authority),
+            AuthorityType::ConfidentialTransferMint,
+            &keypair.pubkey(),
+            &[],
+        )?);
+    }
+
+    let tx = compute_budget::signed_transaction(
+        &rpc,
+        &instructions,
+        &keypair.pubkey(),
+        &[&keypair],
+    )?;
+
+    let signature = rpc.send_and_confirm_transaction_with_spinner(&tx)
+        .context("Failed to update mint confidential transfer config")?;
+
+    status!("\nMint config updated.");
+    status!("Signature: {}", signature);
+
+    Ok(())
+}
+
+fn display_authority(authority: Option<Pubkey>) -> String {
+    authority.map_or_else(|| "none".to_string(), |a| a.to_string())
+}
+
+fn display_auditor(auditor: Option<&ElGamalPubkey>) -> String {
+    auditor.map_or_else(|| "none".to_string(), format_elgamal_pubkey)
+}
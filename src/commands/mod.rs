@@ -1,4 +1,20 @@
+pub mod apply_pending;
+pub mod approve_account;
+pub mod audit;
+pub mod auditor;
 pub mod balance;
+pub mod burn;
 pub mod configure;
 pub mod create;
+pub mod deposit;
 pub mod distribute;
+pub mod enable_credits;
+pub mod estimate;
+pub mod harvest_fees;
+pub mod history;
+pub mod mint_config;
+pub mod pending_approvals;
+pub mod reclaim;
+pub mod repair_balance;
+pub mod transfer;
+pub mod withdraw;
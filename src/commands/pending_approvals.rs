@@ -0,0 +1,78 @@
+use crate::cli::PendingApprovalsArgs;
+use crate::config::create_rpc_client;
+use anyhow::{Context, Result};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::ConfidentialTransferAccount, BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+    state::Account,
+};
+
+/// A configured token account that the confidential transfer authority has not approved yet.
+pub struct PendingApproval {
+    pub account: Pubkey,
+    pub owner: Pubkey,
+}
+
+pub async fn execute(args: PendingApprovalsArgs) -> Result<()> {
+    let rpc = create_rpc_client(&args.rpc);
+
+    status!("Token accounts pending approval for {}...", args.mint);
+
+    let pending = fetch_pending_approvals(&rpc, &args.mint)?;
+
+    if pending.is_empty() {
+        status!("\nNo token accounts are waiting for approval.");
+        return Ok(());
+    }
+
+    status!("\n  {:<44} {:<44}", "Owner", "Token Account");
+    for p in &pending {
+        status!("  {:<44} {:<44}", p.owner, p.account);
+    }
+    status!("\n{} accounts pending", pending.len());
+    status!("Approve with: stealth-launch approve-account --mint {} --wallet <OWNER>", args.mint);
+
+    Ok(())
+}
+
+/// Finds every token account for the mint that is configured for confidential transfers
+/// but not yet approved.
+pub fn fetch_pending_approvals(rpc: &RpcClient, mint: &Pubkey) -> Result<Vec<PendingApproval>> {
+    // Token accounts start with the 32-byte mint
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            0,
+            &mint.to_bytes(),
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let accounts = rpc
+        .get_program_accounts_with_config(&spl_token_2022::id(), config)
+        .context("Failed to query token accounts")?;
+
+    let pending = accounts
+        .into_iter()
+        .filter_map(|(pubkey, account)| {
+            let state = StateWithExtensions::<Account>::unpack(&account.data).ok()?;
+            let ct = state.get_extension::<ConfidentialTransferAccount>().ok()?;
+            (!bool::from(ct.approved)).then_some(PendingApproval {
+                account: pubkey,
+                owner: state.base.owner,
+            })
+        })
+        .collect();
+
+    Ok(pending)
+}
@@ -0,0 +1,147 @@
+use crate::cli::ReclaimArgs;
+use crate::compute_budget;
+use crate::config::{create_rpc_client, expand_path, load_keypair};
+use crate::journal::{DistributionJournal, TransferStatus};
+use crate::output;
+use anyhow::{Context, Result};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
+    signature::Signer,
+};
+use spl_token_2022::solana_zk_sdk::zk_elgamal_proof_program::{
+    self,
+    instruction::{close_context_state, ContextStateInfo},
+};
+use std::collections::HashSet;
+use std::fs;
+
+/// Close instructions are tiny; this keeps each transaction well under the size limit.
+const CLOSES_PER_TRANSACTION: usize = 8;
+
+pub async fn execute(args: ReclaimArgs) -> Result<()> {
+    let keypair = load_keypair(&expand_path(&args.keypair))?;
+    let rpc = create_rpc_client(&args.rpc);
+
+    status!("Searching for proof context accounts owned by {}...", keypair.pubkey());
+
+    // ProofContextState starts with the 32-byte context state authority
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            0,
+            &keypair.pubkey().to_bytes(),
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let mut accounts = rpc
+        .get_program_accounts_with_config(&zk_elgamal_proof_program::id(), config)
+        .context("Failed to query proof context accounts")?;
+
+    // A resumed distribution transfers with the proof accounts it already created
+    let in_flight = in_flight_proof_accounts(&args)?;
+    let found = accounts.len();
+    accounts.retain(|(pubkey, _)| !in_flight.contains(&pubkey.to_string()));
+    if accounts.len() < found {
+        status!(
+            "Keeping {} proof accounts an interrupted distribution still needs (close them by resuming it)",
+            found - accounts.len()
+        );
+    }
+
+    if accounts.is_empty() {
+        status!("\nNo orphaned proof context accounts found.");
+        return Ok(());
+    }
+
+    let total_lamports: u64 = accounts.iter().map(|(_, account)| account.lamports).sum();
+    status!(
+        "Found {} proof context accounts holding {:.9} SOL",
+        accounts.len(),
+        total_lamports as f64 / LAMPORTS_PER_SOL as f64
+    );
+
+    if !args.yes && !output::confirm("Close these accounts?")? {
+        status!("Aborted.");
+        return Ok(());
+    }
+
+    let authority = keypair.pubkey();
+    let mut reclaimed_lamports = 0u64;
+    let mut closed = 0usize;
+
+    for batch in accounts.chunks(CLOSES_PER_TRANSACTION) {
+        let instructions: Vec<_> = batch
+            .iter()
+            .map(|(pubkey, _)| {
+                close_context_state(
+                    ContextStateInfo {
+                        context_state_account: pubkey,
+                        context_state_authority: &authority,
+                    },
+                    &authority,
+                )
+            })
+            .collect();
+
+        let tx = compute_budget::signed_transaction(
+            &rpc,
+            &instructions,
+            &authority,
+            &[&keypair],
+        )?;
+
+        let signature = rpc
+            .send_and_confirm_transaction_with_spinner(&tx)
+            .context("Failed to close proof context accounts")?;
+
+        for (pubkey, account) in batch {
+            status!("  Closed {}", pubkey);
+            reclaimed_lamports += account.lamports;
+        }
+        closed += batch.len();
+        status!("  Signature: {}", signature);
+    }
+
+    status!("\nReclaim complete.");
+    status!("Closed {} accounts", closed);
+    status!(
+        "Recovered: {:.9} SOL",
+        reclaimed_lamports as f64 / LAMPORTS_PER_SOL as f64
+    );
+
+    Ok(())
+}
+
+/// Proof accounts of journal entries stopped after creating them, from every distribution
+/// journal in the working directory plus those passed with `--journal`.
+fn in_flight_proof_accounts(args: &ReclaimArgs) -> Result<HashSet<String>> {
+    let mut journals = Vec::new();
+    for path in &args.journal {
+        journals.push(DistributionJournal::load(&expand_path(path))?);
+    }
+
+    // Other JSON files in the working directory are not journals and are skipped
+    let entries = fs::read_dir(".").context("Failed to read the working directory")?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            if let Ok(journal) = DistributionJournal::load(&path) {
+                journals.push(journal);
+            }
+        }
+    }
+
+    Ok(journals
+        .iter()
+        .flat_map(|journal| &journal.entries)
+        .filter(|entry| entry.status == TransferStatus::ProofAccountsCreated)
+        .flat_map(|entry| entry.proof_accounts.iter().cloned())
+        .collect())
+}
@@ -0,0 +1,168 @@
+use crate::cli::RepairBalanceArgs;
+use crate::commands::balance::{
+    decrypt_available_balance, decrypt_pending_balance, verify_available_balance, PendingBalance,
+};
+use crate::compute_budget;
+use crate::config::{create_rpc_client, expand_path, load_keypair};
+use crate::crypto::ConfidentialKeys;
+use anyhow::{anyhow, Context, Result};
+use solana_sdk::signature::Signer;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::{instruction::apply_pending_balance, ConfidentialTransferAccount},
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    solana_zk_sdk::encryption::auth_encryption::AeCiphertext,
+    state::{Account, Mint},
+};
+
+/// Most 32-bit windows (one full `decrypt_u32` each) a search bounded by the mint's
+/// supply may take, which caps the default bound at 2^48.
+const MAX_DEFAULT_WINDOWS: u64 = 1 << 16;
+
+pub async fn execute(args: RepairBalanceArgs) -> Result<()> {
+    let keypair = load_keypair(&expand_path(&args.keypair))?;
+    let owner_keys = ConfidentialKeys::derive_from_keypair(&keypair)?;
+    let rpc = create_rpc_client(&args.rpc);
+
+    let ata = get_associated_token_address_with_program_id(
+        &keypair.pubkey(),
+        &args.mint,
+        &spl_token_2022::id(),
+    );
+
+    status!("Checking confidential balance...");
+    status!("  Mint: {}", args.mint);
+    status!("  Token Account: {}", ata);
+
+    let account_data = rpc
+        .get_account(&ata)
+        .with_context(|| format!("Token account not found for wallet {}", keypair.pubkey()))?;
+    let account_state = StateWithExtensions::<Account>::unpack(&account_data.data)
+        .context("Failed to unpack token account")?;
+    let ct_account = account_state
+        .get_extension::<ConfidentialTransferAccount>()
+        .context("Confidential transfer extension not found on account - run configure first")?;
+
+    let decryptable: AeCiphertext = ct_account.decryptable_available_balance.try_into()
+        .map_err(|_| anyhow!("Invalid decryptable balance"))?;
+    let decryptable_balance = owner_keys.aes_key.decrypt(&decryptable);
+
+    match decryptable_balance {
+        Some(balance) => status!("  Decryptable balance: {}", balance),
+        None => status!("  Decryptable balance: could not decrypt"),
+    }
+
+    if let Some(balance) = decryptable_balance {
+        if verify_available_balance(ct_account, &owner_keys.elgamal_keypair, balance)? {
+            status!("\nDecryptable balance matches the encrypted available balance; nothing to repair.");
+            return Ok(());
+        }
+    }
+
+    let max_amount = match args.max_amount {
+        Some(max_amount) => max_amount,
+        None => {
+            let mint_account_data = rpc.get_account(&args.mint)?;
+            let mint_state = StateWithExtensions::<Mint>::unpack(&mint_account_data.data)?;
+            default_search_bound(mint_state.base.supply).map_err(|e| {
+                anyhow!(
+                    "Mint {}: {} - pass --max-amount with the most this account could hold",
+                    args.mint,
+                    e
+                )
+            })?
+        }
+    };
+
+    status!("  Decrypting encrypted available balance (up to {})...", max_amount);
+    let available = decrypt_available_balance(ct_account, &owner_keys.elgamal_keypair, max_amount)?
+        .ok_or_else(|| {
+            anyhow!(
+                "Could not decrypt the available balance within 0..={} - you may not be the owner, or try a larger --max-amount",
+                max_amount
+            )
+        })?;
+    status!("  Encrypted available balance: {}", available);
+
+    // The only instruction that rewrites the decryptable balance is ApplyPendingBalance,
+    // which also merges whatever is pending, so the new balance has to include it
+    let PendingBalance::Decrypted(pending) =
+        decrypt_pending_balance(ct_account, &owner_keys.elgamal_keypair)?
+    else {
+        return Err(anyhow!("Failed to decrypt pending balance - you may not be the owner"));
+    };
+    let credit_counter = u64::from(ct_account.pending_balance_credit_counter);
+    if pending > 0 {
+        status!("  Pending balance {} will be applied as well", pending);
+    }
+
+    let new_available_balance = available
+        .checked_add(pending)
+        .ok_or_else(|| anyhow!("Available balance overflow"))?;
+    let new_decryptable_balance = owner_keys.aes_key.encrypt(new_available_balance);
+
+    let repair_ix = apply_pending_balance(
+        &spl_token_2022::id(),
+        &ata,
+        credit_counter,
+        &new_decryptable_balance.into(),
+        &keypair.pubkey(),
+        &[],
+    )?;
+
+    let tx = compute_budget::signed_transaction(
+        &rpc,
+        &[repair_ix],
+        &keypair.pubkey(),
+        &[&keypair],
+    )?;
+
+    let signature = rpc.send_and_confirm_transaction_with_spinner(&tx)
+        .context("Failed to rewrite decryptable balance")?;
+
+    status!("\nDecryptable balance repaired.");
+    status!("Signature: {}", signature);
+    status!("Available balance: {}", new_available_balance);
+
+    Ok(())
+}
+
+/// The search bound used without `--max-amount`: the mint's public supply, unless there is
+/// none (confidential supply mints) or it needs more than [`MAX_DEFAULT_WINDOWS`] windows.
+fn default_search_bound(supply: u64) -> Result<u64> {
+    if supply == 0 {
+        return Err(anyhow!("no public supply to bound the search (confidential supply mint)"));
+    }
+    if supply >> 32 >= MAX_DEFAULT_WINDOWS {
+        return Err(anyhow!(
+            "supply {} is too large to search for the balance in reasonable time",
+            supply
+        ));
+    }
+    Ok(supply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_bound_is_the_supply() {
+        assert_eq!(default_search_bound(1_000_000).unwrap(), 1_000_000);
+        assert_eq!(default_search_bound((1 << 48) - 1).unwrap(), (1 << 48) - 1);
+    }
+
+    #[test]
+    fn default_bound_is_capped_at_the_window_budget() {
+        assert!(default_search_bound(1 << 48).is_err());
+        // 1e9 tokens at 9 decimals
+        assert!(default_search_bound(1_000_000_000_000_000_000).is_err());
+    }
+
+    #[test]
+    fn default_bound_needs_a_public_supply() {
+        assert!(default_search_bound(0).is_err());
+    }
+}
@@ -0,0 +1,117 @@
+use crate::cli::TransferArgs;
+use crate::commands::distribute::{
+    check_readiness, fetch_mint_transfer_config, transfer_to_wallet, Readiness, TransferSource,
+};
+use crate::compute_budget;
+use crate::config::{expand_path, load_keypair};
+use crate::crypto::ConfidentialKeys;
+use crate::journal::{DistributionJournal, JournalEntry};
+use anyhow::{anyhow, Result};
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::signature::Signer;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::extension::{
+    confidential_transfer::ConfidentialTransferAccount,
+    BaseStateWithExtensions, StateWithExtensions,
+};
+use spl_token_2022::solana_zk_sdk::encryption::auth_encryption::AeCiphertext;
+use spl_token_client::{
+    client::{ProgramRpcClient, ProgramRpcClientSendTransaction},
+    token::Token,
+};
+use std::sync::Arc;
+
+pub async fn execute(args: TransferArgs) -> Result<()> {
+    let keypair = load_keypair(&expand_path(&args.keypair))?;
+
+    let rpc_client = Arc::new(NonblockingRpcClient::new_with_commitment(
+        args.rpc.clone(),
+        CommitmentConfig::confirmed(),
+    ));
+
+    let program_client = Arc::new(ProgramRpcClient::new(
+        rpc_client.clone(),
+        ProgramRpcClientSendTransaction,
+    ));
+
+    let authority_keys = ConfidentialKeys::derive_from_keypair(&keypair)?;
+
+    let source_ata = get_associated_token_address_with_program_id(
+        &keypair.pubkey(),
+        &args.mint,
+        &spl_token_2022::id(),
+    );
+
+    let source_account_data = rpc_client.get_account(&source_ata).await?;
+    let source_state =
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&source_account_data.data)?;
+    let source_ct = source_state.get_extension::<ConfidentialTransferAccount>()?;
+
+    let source_decryptable: AeCiphertext = source_ct.decryptable_available_balance.try_into()
+        .map_err(|_| anyhow!("Invalid source decryptable balance"))?;
+    let current_balance = authority_keys.aes_key
+        .decrypt(&source_decryptable)
+        .ok_or_else(|| anyhow!("Failed to decrypt source balance"))?;
+
+    if args.amount > current_balance {
+        return Err(anyhow!(
+            "Insufficient balance. Need {}, have {}",
+            args.amount,
+            current_balance
+        ));
+    }
+
+    let readiness = check_readiness(&rpc_client, &args.mint, &[args.to.to_string()]).await?;
+    if readiness[0] != Readiness::Ready {
+        return Err(anyhow!(
+            "Recipient {} cannot receive confidential transfers: {}",
+            args.to,
+            readiness[0].label()
+        ));
+    }
+
+    let (decimals, auditor_elgamal_pubkey, transfer_fee) =
+        fetch_mint_transfer_config(&rpc_client, &args.mint).await?;
+
+    let token = compute_budget::budgeted_token(
+        Token::new(
+            program_client,
+            &spl_token_2022::id(),
+            &args.mint,
+            Some(decimals),
+            Arc::new(keypair.insecure_clone()),
+        ),
+        &rpc_client,
+        &[source_ata],
+    )
+    .await?;
+
+    let source = TransferSource {
+        rpc_client: &rpc_client,
+        token: &token,
+        authority: &keypair,
+        authority_keys: &authority_keys,
+        source_ata,
+        mint: args.mint,
+        auditor_elgamal_pubkey,
+        transfer_fee,
+    };
+
+    status!("Transferring {} to {}", args.amount, args.to);
+
+    // A single transfer keeps its journal in memory only
+    let mut journal = DistributionJournal {
+        mint: args.mint.to_string(),
+        authority: keypair.pubkey().to_string(),
+        entries: vec![JournalEntry::new(args.to.to_string(), args.amount)],
+        path: None,
+    };
+
+    transfer_to_wallet(&source, &mut journal, 0, args.memo.as_deref()).await?;
+
+    status!("\nTransfer complete.");
+    status!("Remaining balance: {}", current_balance - args.amount);
+
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use crate::cli::WithdrawArgs;
+use crate::commands::balance::decode_balances;
+use crate::compute_budget;
+use crate::config::{expand_path, load_keypair};
+use crate::crypto::ConfidentialKeys;
+use anyhow::{anyhow, Context, Result};
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::signature::{Keypair, Signer};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::extension::{
+    confidential_mint_burn::ConfidentialMintBurn,
+    confidential_transfer::{account_info::WithdrawAccountInfo, ConfidentialTransferAccount},
+    BaseStateWithExtensions, StateWithExtensions,
+};
+use spl_token_2022::solana_zk_sdk::encryption::auth_encryption::AeCiphertext;
+use spl_token_client::{
+    client::{ProgramRpcClient, ProgramRpcClientSendTransaction},
+    token::Token,
+};
+use spl_token_confidential_transfer_proof_generation::withdraw::withdraw_proof_data;
+use std::sync::Arc;
+
+pub async fn execute(args: WithdrawArgs) -> Result<()> {
+    let keypair = load_keypair(&expand_path(&args.keypair))?;
+    let owner_keys = ConfidentialKeys::derive_from_keypair(&keypair)?;
+
+    let rpc_client = Arc::new(NonblockingRpcClient::new_with_commitment(
+        args.rpc.clone(),
+        CommitmentConfig::confirmed(),
+    ));
+
+    let program_client = Arc::new(ProgramRpcClient::new(
+        rpc_client.clone(),
+        ProgramRpcClientSendTransaction,
+    ));
+
+    let ata = get_associated_token_address_with_program_id(
+        &keypair.pubkey(),
+        &args.mint,
+        &spl_token_2022::id(),
+    );
+
+    status!("Withdrawing from confidential balance...");
+    status!("  Mint: {}", args.mint);
+    status!("  Token Account: {}", ata);
+    status!("  Amount: {}", args.amount);
+
+    let mint_account_data = rpc_client.get_account(&args.mint).await?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_account_data.data)?;
+    if mint_state.get_extension::<ConfidentialMintBurn>().is_ok() {
+        return Err(anyhow!(
+            "Withdraw is disabled on mint {} - its supply is managed by confidential mint/burn",
+            args.mint
+        ));
+    }
+    let decimals = mint_state.base.decimals;
+
+    let account_data = rpc_client
+        .get_account(&ata)
+        .await
+        .with_context(|| format!("Token account not found for wallet {}", keypair.pubkey()))?;
+    let account_state =
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)
+            .context("Failed to unpack token account")?;
+    let ct_account = account_state
+        .get_extension::<ConfidentialTransferAccount>()
+        .context("Confidential transfer extension not found on account")?;
+
+    let available_balance = ct_account.available_balance.try_into()
+        .map_err(|_| anyhow!("Invalid available balance ciphertext"))?;
+    let decryptable_balance: AeCiphertext = ct_account.decryptable_available_balance.try_into()
+        .map_err(|_| anyhow!("Invalid decryptable balance ciphertext"))?;
+    let current_balance = owner_keys.aes_key
+        .decrypt(&decryptable_balance)
+        .ok_or_else(|| anyhow!("Failed to decrypt balance - you may not be the owner"))?;
+
+    if args.amount > current_balance {
+        return Err(anyhow!(
+            "Insufficient confidential balance. Need {}, have {}",
+            args.amount,
+            current_balance
+        ));
+    }
+
+    status!("  Generating proofs...");
+    let proof_data = withdraw_proof_data(
+        &available_balance,
+        current_balance,
+        args.amount,
+        &owner_keys.elgamal_keypair,
+    ).map_err(|e| anyhow!("Failed to generate proof data: {:?}", e))?;
+
+    let token = compute_budget::budgeted_token(
+        Token::new(
+            program_client,
+            &spl_token_2022::id(),
+            &args.mint,
+            Some(decimals),
+            Arc::new(keypair.insecure_clone()),
+        ),
+        &rpc_client,
+        &[ata],
+    )
+    .await?;
+
+    let equality_proof_keypair = Keypair::new();
+    let range_proof_keypair = Keypair::new();
+
+    status!("  Creating proof context accounts...");
+
+    token.confidential_transfer_create_context_state_account(
+        &equality_proof_keypair.pubkey(),
+        &keypair.pubkey(),
+        &proof_data.equality_proof_data,
+        false,
+        &[&keypair, &equality_proof_keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to create equality proof account: {}", e))?;
+
+    // Range proofs are large, split account creation and proof verification
+    token.confidential_transfer_create_context_state_account(
+        &range_proof_keypair.pubkey(),
+        &keypair.pubkey(),
+        &proof_data.range_proof_data,
+        true,
+        &[&keypair, &range_proof_keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to create range proof account: {}", e))?;
+
+    status!("  Executing withdraw...");
+    token.confidential_transfer_withdraw(
+        &ata,
+        &keypair.pubkey(),
+        Some(&equality_proof_keypair.pubkey()),
+        Some(&range_proof_keypair.pubkey()),
+        args.amount,
+        decimals,
+        Some(WithdrawAccountInfo::new(ct_account)),
+        &owner_keys.elgamal_keypair,
+        &owner_keys.aes_key,
+        &[&keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to execute confidential withdraw: {}", e))?;
+
+    // Close context state accounts to recover rent
+    status!("  Cleaning up proof accounts...");
+    token.confidential_transfer_close_context_state_account(
+        &equality_proof_keypair.pubkey(),
+        &ata,
+        &keypair.pubkey(),
+        &[&keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to close equality proof account: {}", e))?;
+
+    token.confidential_transfer_close_context_state_account(
+        &range_proof_keypair.pubkey(),
+        &ata,
+        &keypair.pubkey(),
+        &[&keypair],
+    ).await
+    .map_err(|e| anyhow!("Failed to close range proof account: {}", e))?;
+
+    let account_data = rpc_client.get_account(&ata).await?;
+    let balances = decode_balances(&account_data.data, &owner_keys)?;
+
+    status!("\nWithdraw complete.");
+    status!("Public balance: {}", balances.public);
+    status!("Confidential balance: {}", balances.available);
+
+    Ok(())
+}
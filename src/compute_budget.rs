@@ -0,0 +1,164 @@
+use crate::cli::PriorityFee;
+use anyhow::{Context, Result};
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_response::RpcPrioritizationFee;
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::Keypair,
+    transaction::Transaction,
+};
+use spl_token_client::{
+    client::ProgramRpcClientSendTransaction,
+    token::{ComputeUnitLimit, Token},
+};
+use std::sync::OnceLock;
+
+/// `--priority-fee auto` pays this percentile of the fees recently paid to lock the same
+/// accounts.
+const AUTO_FEE_PERCENTILE: usize = 75;
+
+#[derive(Clone, Copy, Default)]
+struct Settings {
+    priority_fee: Option<PriorityFee>,
+    compute_unit_limit: Option<u32>,
+}
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+pub fn init(priority_fee: Option<PriorityFee>, compute_unit_limit: Option<u32>) {
+    let _ = SETTINGS.set(Settings {
+        priority_fee,
+        compute_unit_limit,
+    });
+}
+
+fn settings() -> Settings {
+    SETTINGS.get().copied().unwrap_or_default()
+}
+
+/// The `--compute-unit-limit` every transaction requests, if one was given.
+pub fn compute_unit_limit() -> Option<u32> {
+    settings().compute_unit_limit
+}
+
+/// The compute unit price in micro-lamports for a transaction locking `accounts`: the
+/// `--priority-fee` value, or with `auto` a percentile of recent fees for those accounts.
+pub fn compute_unit_price(rpc: &RpcClient, accounts: &[Pubkey]) -> Result<Option<u64>> {
+    match settings().priority_fee {
+        None => Ok(None),
+        Some(PriorityFee::MicroLamports(price)) => Ok(Some(price)),
+        Some(PriorityFee::Auto) => {
+            let fees = rpc
+                .get_recent_prioritization_fees(accounts)
+                .context("Failed to sample recent prioritization fees")?;
+            Ok(Some(auto_price(fees)))
+        }
+    }
+}
+
+/// [`compute_unit_price`] for commands on the nonblocking client.
+pub async fn compute_unit_price_nonblocking(
+    rpc: &NonblockingRpcClient,
+    accounts: &[Pubkey],
+) -> Result<Option<u64>> {
+    match settings().priority_fee {
+        None => Ok(None),
+        Some(PriorityFee::MicroLamports(price)) => Ok(Some(price)),
+        Some(PriorityFee::Auto) => {
+            let fees = rpc
+                .get_recent_prioritization_fees(accounts)
+                .await
+                .context("Failed to sample recent prioritization fees")?;
+            Ok(Some(auto_price(fees)))
+        }
+    }
+}
+
+fn auto_price(fees: Vec<RpcPrioritizationFee>) -> u64 {
+    let mut fees: Vec<u64> = fees.into_iter().map(|f| f.prioritization_fee).collect();
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+    fees[(fees.len() - 1) * AUTO_FEE_PERCENTILE / 100]
+}
+
+/// `instructions` prefixed with the compute budget instructions for `price`.
+fn with_budget(instructions: &[Instruction], price: Option<u64>) -> Vec<Instruction> {
+    let mut budgeted = Vec::with_capacity(instructions.len() + 2);
+    if let Some(limit) = compute_unit_limit() {
+        budgeted.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+    }
+    if let Some(price) = price.filter(|price| *price > 0) {
+        budgeted.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+    }
+    budgeted.extend_from_slice(instructions);
+    budgeted
+}
+
+/// The accounts a transaction write-locks, which are what priority fees compete for.
+pub fn writable_accounts(instructions: &[Instruction], payer: &Pubkey) -> Vec<Pubkey> {
+    let mut writable = vec![*payer];
+    for account in instructions.iter().flat_map(|ix| &ix.accounts) {
+        if account.is_writable && !writable.contains(&account.pubkey) {
+            writable.push(account.pubkey);
+        }
+    }
+    writable
+}
+
+/// Signs `instructions` into a transaction with the compute budget from `--priority-fee`
+/// and `--compute-unit-limit`. Every transaction this tool builds itself goes through here.
+pub fn signed_transaction(
+    rpc: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    signers: &[&Keypair],
+) -> Result<Transaction> {
+    let price = compute_unit_price(rpc, &writable_accounts(instructions, payer))?;
+    let recent_blockhash = rpc.get_latest_blockhash()?;
+    Ok(Transaction::new_signed_with_payer(
+        &with_budget(instructions, price),
+        Some(payer),
+        signers,
+        recent_blockhash,
+    ))
+}
+
+/// [`signed_transaction`] for commands on the nonblocking client.
+pub async fn signed_transaction_nonblocking(
+    rpc: &NonblockingRpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    signers: &[&Keypair],
+) -> Result<Transaction> {
+    let price =
+        compute_unit_price_nonblocking(rpc, &writable_accounts(instructions, payer)).await?;
+    let recent_blockhash = rpc.get_latest_blockhash().await?;
+    Ok(Transaction::new_signed_with_payer(
+        &with_budget(instructions, price),
+        Some(payer),
+        signers,
+        recent_blockhash,
+    ))
+}
+
+/// Applies the same compute budget to every transaction a `Token` client sends. With
+/// `auto`, fees are sampled once for `accounts`, usually the token account it works on.
+pub async fn budgeted_token(
+    token: Token<ProgramRpcClientSendTransaction>,
+    rpc: &NonblockingRpcClient,
+    accounts: &[Pubkey],
+) -> Result<Token<ProgramRpcClientSendTransaction>> {
+    let mut token = token;
+    if let Some(limit) = compute_unit_limit() {
+        token = token.with_compute_unit_limit(ComputeUnitLimit::Static(limit));
+    }
+    if let Some(price) = compute_unit_price_nonblocking(rpc, accounts).await? {
+        token = token.with_compute_unit_price(price);
+    }
+    Ok(token)
+}
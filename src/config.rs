@@ -2,7 +2,9 @@ use anyhow::{Context, Result};
 use solana_client::rpc_client::RpcClient;
 use solana_commitment_config::CommitmentConfig;
 use solana_sdk::signature::Keypair;
+use spl_token_2022::solana_zk_sdk::encryption::elgamal::ElGamalKeypair;
 use std::fs;
+use std::io::Write;
 use std::path::Path;
 
 pub fn load_keypair(path: &Path) -> Result<Keypair> {
@@ -32,3 +34,62 @@ fn expand_tilde(path: &Path) -> std::path::PathBuf {
 pub fn expand_path(path: &Path) -> std::path::PathBuf {
     expand_tilde(path)
 }
+
+pub fn save_keypair(keypair: &Keypair, path: &Path, overwrite: bool) -> Result<()> {
+    let data = serde_json::to_string(&keypair.to_bytes().to_vec())?;
+    write_secret_file(path, &data, overwrite)
+}
+
+/// Reads an ElGamal keypair written by `save_elgamal_keypair` (JSON array of 64 bytes).
+pub fn load_elgamal_keypair(path: &Path) -> Result<ElGamalKeypair> {
+    let path = expand_tilde(path);
+    let data = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read ElGamal keypair from {}", path.display()))?;
+
+    let bytes: Vec<u8> = serde_json::from_str(&data)
+        .with_context(|| "Invalid ElGamal keypair format - expected JSON array of bytes")?;
+
+    ElGamalKeypair::try_from(bytes.as_slice())
+        .map_err(|_| anyhow::anyhow!("Invalid ElGamal keypair bytes"))
+}
+
+pub fn save_elgamal_keypair(keypair: &ElGamalKeypair, path: &Path, overwrite: bool) -> Result<()> {
+    let bytes: [u8; 64] = keypair.into();
+    let data = serde_json::to_string(&bytes.to_vec())?;
+    write_secret_file(path, &data, overwrite)
+}
+
+/// Writes key material readable by the current user only. The file is created with that
+/// mode, so the key is never readable by others, even briefly. Fails if the file exists
+/// unless `overwrite` is set.
+fn write_secret_file(path: &Path, data: &str, overwrite: bool) -> Result<()> {
+    let path = expand_tilde(path);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true);
+    if overwrite {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(&path)
+        .with_context(|| format!("Failed to write keypair to {}", path.display()))?;
+
+    // An overwritten file keeps its old mode, so restrict it before the key goes in
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))
+            .with_context(|| format!("Failed to restrict permissions on {}", path.display()))?;
+    }
+
+    file.write_all(data.as_bytes())
+        .with_context(|| format!("Failed to write keypair to {}", path.display()))
+}
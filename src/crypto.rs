@@ -24,7 +24,6 @@ impl ConfidentialKeys {
         })
     }
 
-    #[allow(dead_code)]
     pub fn elgamal_pubkey(&self) -> ElGamalPubkey {
         *self.elgamal_keypair.pubkey()
     }
@@ -42,3 +41,8 @@ pub fn parse_elgamal_pubkey(s: &str) -> Result<ElGamalPubkey> {
     ElGamalPubkey::try_from(bytes.as_slice())
         .map_err(|_| anyhow!("Invalid ElGamal pubkey bytes"))
 }
+
+/// Formats an ElGamal pubkey as base58, the same encoding `parse_elgamal_pubkey` accepts.
+pub fn format_elgamal_pubkey(pubkey: &ElGamalPubkey) -> String {
+    bs58::encode(<[u8; 32]>::from(pubkey)).into_string()
+}
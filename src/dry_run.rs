@@ -0,0 +1,179 @@
+use crate::compute_budget;
+use crate::output;
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig};
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Keypair};
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// Outcome of one transaction in a `--dry-run`.
+#[derive(Serialize)]
+pub struct SimulatedStep {
+    pub step: String,
+    /// Why the step was not simulated, typically because it depends on an earlier step
+    /// that a dry run never lands
+    pub skipped: Option<String>,
+    pub error: Option<String>,
+    pub compute_units: Option<u64>,
+    pub fee: u64,
+    /// Lamports moved into accounts the transaction creates
+    pub rent: u64,
+    pub created_accounts: Vec<String>,
+    pub logs: Vec<String>,
+}
+
+/// Collects the simulated transactions of a command run with `--dry-run`. Nothing is
+/// ever submitted.
+#[derive(Serialize, Default)]
+pub struct DryRun {
+    pub steps: Vec<SimulatedStep>,
+}
+
+impl DryRun {
+    /// Signs `instructions` into a transaction and runs `simulateTransaction`, recording
+    /// compute units, logs, fee and the accounts (and rent) the transaction would create.
+    /// Returns whether the simulation succeeded.
+    pub fn simulate(
+        &mut self,
+        rpc: &RpcClient,
+        step: &str,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        signers: &[&Keypair],
+    ) -> Result<bool> {
+        let tx = compute_budget::signed_transaction(rpc, instructions, payer, signers)?;
+        let fee = rpc
+            .get_fee_for_message(&tx.message)
+            .context("Failed to estimate transaction fee")?;
+
+        // Post-simulation state of every writable account shows what gets created
+        let writable = compute_budget::writable_accounts(instructions, payer);
+        let existing = rpc.get_multiple_accounts(&writable)?;
+
+        let result = rpc
+            .simulate_transaction_with_config(
+                &tx,
+                RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    accounts: Some(RpcSimulateTransactionAccountsConfig {
+                        encoding: Some(UiAccountEncoding::Base64),
+                        addresses: writable.iter().map(|p| p.to_string()).collect(),
+                    }),
+                    ..Default::default()
+                },
+            )
+            .with_context(|| format!("Failed to simulate {}", step))?
+            .value;
+
+        let mut created_accounts = Vec::new();
+        let mut rent = 0;
+        let post_accounts = result.accounts.unwrap_or_default();
+        for ((address, before), after) in writable.iter().zip(&existing).zip(&post_accounts) {
+            if let (None, Some(after)) = (before, after) {
+                created_accounts.push(address.to_string());
+                rent += after.lamports;
+            }
+        }
+
+        let simulated = SimulatedStep {
+            step: step.to_string(),
+            skipped: None,
+            error: result.err.map(|e| format!("{:?}", e)),
+            compute_units: result.units_consumed,
+            fee,
+            rent,
+            created_accounts,
+            logs: result.logs.unwrap_or_default(),
+        };
+
+        match &simulated.error {
+            None => status!(
+                "[dry-run] {}: ok, {} compute units, fee {} lamports, rent {} lamports",
+                step,
+                simulated.compute_units.unwrap_or_default(),
+                fee,
+                rent
+            ),
+            Some(error) => status!("[dry-run] {}: FAILED - {}", step, error),
+        }
+        for account in &simulated.created_accounts {
+            status!("  creates {}", account);
+        }
+        for log in &simulated.logs {
+            status!("  | {}", log);
+        }
+
+        let success = simulated.error.is_none();
+        self.steps.push(simulated);
+        Ok(success)
+    }
+
+    /// Records a check run locally instead of through the RPC, such as verifying proofs
+    /// before they are sent. Returns whether it passed.
+    pub fn check(&mut self, step: &str, result: Result<()>) -> bool {
+        let error = result.err().map(|e| format!("{:#}", e));
+        match &error {
+            None => status!("[dry-run] {}: ok", step),
+            Some(error) => status!("[dry-run] {}: FAILED - {}", step, error),
+        }
+        let passed = error.is_none();
+        self.steps.push(SimulatedStep {
+            step: step.to_string(),
+            skipped: None,
+            error,
+            compute_units: None,
+            fee: 0,
+            rent: 0,
+            created_accounts: Vec::new(),
+            logs: Vec::new(),
+        });
+        passed
+    }
+
+    /// Records a step that would run but cannot be simulated.
+    pub fn skip(&mut self, step: &str, reason: &str) {
+        status!("[dry-run] {}: not simulated - {}", step, reason);
+        self.steps.push(SimulatedStep {
+            step: step.to_string(),
+            skipped: Some(reason.to_string()),
+            error: None,
+            compute_units: None,
+            fee: 0,
+            rent: 0,
+            created_accounts: Vec::new(),
+            logs: Vec::new(),
+        });
+    }
+
+    /// Prints the totals and emits the `--output json` document. Fails if any simulated
+    /// transaction failed, so scripts can gate on the exit code.
+    pub fn finish(&self) -> Result<()> {
+        let simulated = self.steps.iter().filter(|s| s.skipped.is_none()).count();
+        let failed = self.steps.iter().filter(|s| s.error.is_some()).count();
+        let fees: u64 = self.steps.iter().map(|s| s.fee).sum();
+        let rent: u64 = self.steps.iter().map(|s| s.rent).sum();
+
+        status!("\nDry run - nothing was sent.");
+        status!(
+            "Simulated: {}, failed: {}, not simulated: {}",
+            simulated,
+            failed,
+            self.steps.len() - simulated
+        );
+        status!("Fees: {} lamports", fees);
+        status!("Rent: {} lamports", rent);
+        status!("Total cost: {:.9} SOL", (fees + rent) as f64 / LAMPORTS_PER_SOL);
+
+        output::emit(self)?;
+
+        if failed > 0 {
+            return Err(anyhow!("{} simulated transactions failed", failed));
+        }
+        Ok(())
+    }
+}
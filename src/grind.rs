@@ -0,0 +1,109 @@
+use anyhow::{anyhow, Result};
+use solana_sdk::signature::{Keypair, Signer};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Instant;
+
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Longest prefix plus suffix. Each character multiplies the expected attempts by 58:
+/// five is ~6.6e8 attempts (minutes to an hour on a desktop), six is already ~3.8e10.
+const MAX_PATTERN_LEN: usize = 5;
+
+/// Searches for a keypair whose base58 address starts with `prefix` and ends with `suffix`,
+/// using every available core.
+pub fn grind_keypair(prefix: Option<&str>, suffix: Option<&str>) -> Result<Keypair> {
+    let prefix = prefix.unwrap_or_default().to_string();
+    let suffix = suffix.unwrap_or_default().to_string();
+
+    for c in prefix.chars().chain(suffix.chars()) {
+        if !BASE58_ALPHABET.contains(c) {
+            return Err(anyhow!("'{}' is not a valid base58 character", c));
+        }
+    }
+
+    let pattern_len = prefix.len() + suffix.len();
+    if pattern_len > MAX_PATTERN_LEN {
+        return Err(anyhow!(
+            "Grind pattern too long (max {} characters combined)",
+            MAX_PATTERN_LEN
+        ));
+    }
+    let expected_attempts = 58u64.pow(pattern_len as u32);
+
+    let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let found = Arc::new(AtomicBool::new(false));
+    let attempts = Arc::new(AtomicU64::new(0));
+    let (tx, rx) = mpsc::channel();
+    let start = Instant::now();
+
+    status!(
+        "Grinding mint address (prefix: '{}', suffix: '{}') on {} threads, ~{} attempts expected...",
+        prefix, suffix, threads, expected_attempts
+    );
+
+    for _ in 0..threads {
+        let found = found.clone();
+        let attempts = attempts.clone();
+        let tx = tx.clone();
+        let prefix = prefix.clone();
+        let suffix = suffix.clone();
+
+        thread::spawn(move || {
+            while !found.load(Ordering::Relaxed) {
+                let keypair = Keypair::new();
+                let address = keypair.pubkey().to_string();
+                attempts.fetch_add(1, Ordering::Relaxed);
+
+                if address.starts_with(&prefix) && address.ends_with(&suffix) {
+                    if !found.swap(true, Ordering::Relaxed) {
+                        let _ = tx.send(keypair);
+                    }
+                    return;
+                }
+            }
+        });
+    }
+    drop(tx);
+
+    let keypair = rx
+        .recv()
+        .map_err(|_| anyhow!("Grind threads exited without finding a match"))?;
+
+    status!(
+        "  Found {} after {} attempts in {:.1}s",
+        keypair.pubkey(),
+        attempts.load(Ordering::Relaxed),
+        start.elapsed().as_secs_f64()
+    );
+
+    Ok(keypair)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_characters_outside_base58() {
+        for pattern in ["0", "O", "I", "l", "ab-c"] {
+            assert!(grind_keypair(Some(pattern), None).is_err(), "prefix {}", pattern);
+            assert!(grind_keypair(None, Some(pattern)).is_err(), "suffix {}", pattern);
+        }
+    }
+
+    #[test]
+    fn rejects_patterns_over_five_characters() {
+        assert!(grind_keypair(Some("abc"), Some("def")).is_err());
+        assert!(grind_keypair(Some("123456"), None).is_err());
+    }
+
+    #[test]
+    fn finds_address_matching_prefix_and_suffix() {
+        let keypair = grind_keypair(Some("A"), Some("z")).unwrap();
+        let address = keypair.pubkey().to_string();
+        assert!(address.starts_with('A'));
+        assert!(address.ends_with('z'));
+    }
+}
@@ -0,0 +1,100 @@
+use crate::receipt::{response_signature, StepSignature};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use spl_token_client::client::RpcClientResponse;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Per-run record of a distribution, rewritten after every step so an interrupted run
+/// can be resumed without paying anyone twice.
+#[derive(Serialize, Deserialize)]
+pub struct DistributionJournal {
+    pub mint: String,
+    pub authority: String,
+    pub entries: Vec<JournalEntry>,
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub wallet: String,
+    pub amount: u64,
+    pub status: TransferStatus,
+    /// Decrypted source balance right before proof generation; compared against the
+    /// current balance on resume to tell whether the transfer itself landed.
+    pub source_balance_before: Option<u64>,
+    /// Proof context state accounts that may still hold rent.
+    pub proof_accounts: Vec<String>,
+    pub signatures: Vec<StepSignature>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferStatus {
+    Pending,
+    ProofAccountsCreated,
+    Transferred,
+    Completed,
+    /// Recipient was not ready and was moved to the pending queue
+    Parked,
+}
+
+impl JournalEntry {
+    pub fn new(wallet: String, amount: u64) -> Self {
+        Self {
+            wallet,
+            amount,
+            status: TransferStatus::Pending,
+            source_balance_before: None,
+            proof_accounts: Vec::new(),
+            signatures: Vec::new(),
+            error: None,
+        }
+    }
+}
+
+impl DistributionJournal {
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read journal from {}", path.display()))?;
+        let mut journal: Self = serde_json::from_str(&data).context("Invalid distribution journal")?;
+        journal.path = Some(path.to_path_buf());
+        Ok(journal)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let data = serde_json::to_string_pretty(self)?;
+        // Write to a temp file and rename so a crash mid-write never corrupts the journal
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, data)
+            .with_context(|| format!("Failed to write journal to {}", tmp.display()))?;
+        fs::rename(&tmp, path)
+            .with_context(|| format!("Failed to write journal to {}", path.display()))
+    }
+
+    pub fn record_response(
+        &mut self,
+        index: usize,
+        step: &str,
+        response: RpcClientResponse,
+    ) -> Result<()> {
+        if let Some(signature) = response_signature(response) {
+            self.entries[index].signatures.push(StepSignature {
+                step: step.to_string(),
+                signature: signature.to_string(),
+            });
+        }
+        self.save()
+    }
+
+    pub fn set_status(&mut self, index: usize, status: TransferStatus) -> Result<()> {
+        self.entries[index].status = status;
+        self.save()
+    }
+}
@@ -1,21 +1,77 @@
+// Declared first so the `status!` macro is visible in the modules below
+#[macro_use]
+mod output;
+
 mod cli;
 mod commands;
+mod compute_budget;
 mod config;
 mod crypto;
+mod dry_run;
 mod error;
+mod grind;
+mod journal;
+mod receipt;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::Parser;
 use cli::{Cli, Commands};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    output::init(cli.output);
+    compute_budget::init(cli.priority_fee, cli.compute_unit_limit);
+
+    let dry_run = cli.dry_run;
+    let supports_dry_run = matches!(
+        cli.command,
+        Commands::Create(_) | Commands::Configure(_) | Commands::Distribute(_)
+    );
+
+    let supports_json = matches!(
+        cli.command,
+        Commands::Create(_)
+            | Commands::Configure(_)
+            | Commands::Distribute(_)
+            | Commands::Estimate(_)
+            | Commands::Balance(_)
+    );
+
+    let result = if dry_run && !supports_dry_run {
+        Err(anyhow!("--dry-run is only supported by create, configure and distribute"))
+    } else if output::is_json() && !supports_json {
+        Err(anyhow!(
+            "--output json is only supported by create, configure, distribute, estimate and balance"
+        ))
+    } else {
+        match cli.command {
+            Commands::Create(args) => commands::create::execute(args, dry_run).await,
+            Commands::Configure(args) => commands::configure::execute(args, dry_run).await,
+            Commands::Distribute(args) => commands::distribute::execute(args, dry_run).await,
+            Commands::Estimate(args) => commands::estimate::execute(args).await,
+            Commands::Balance(args) => commands::balance::execute(args).await,
+            Commands::ApplyPending(args) => commands::apply_pending::execute(args).await,
+            Commands::EnableCredits(args) => commands::enable_credits::execute(args).await,
+            Commands::Withdraw(args) => commands::withdraw::execute(args).await,
+            Commands::Deposit(args) => commands::deposit::execute(args).await,
+            Commands::Transfer(args) => commands::transfer::execute(args).await,
+            Commands::Reclaim(args) => commands::reclaim::execute(args).await,
+            Commands::RepairBalance(args) => commands::repair_balance::execute(args).await,
+            Commands::Burn(args) => commands::burn::execute(args).await,
+            Commands::HarvestFees(args) => commands::harvest_fees::execute(args).await,
+            Commands::History(args) => commands::history::execute(args).await,
+            Commands::ApproveAccount(args) => commands::approve_account::execute(args).await,
+            Commands::PendingApprovals(args) => commands::pending_approvals::execute(args).await,
+            Commands::MintConfig(args) => commands::mint_config::execute(args).await,
+            Commands::Audit(args) => commands::audit::execute(args).await,
+            Commands::AuditorKeygen(args) => commands::auditor::keygen(args),
+            Commands::Auditor(args) => commands::auditor::execute(args).await,
+        }
+    };
 
-    match cli.command {
-        Commands::Create(args) => commands::create::execute(args).await,
-        Commands::Configure(args) => commands::configure::execute(args).await,
-        Commands::Distribute(args) => commands::distribute::execute(args).await,
-        Commands::Balance(args) => commands::balance::execute(args).await,
+    if let Err(e) = &result {
+        output::emit_error(e);
     }
+    result
 }
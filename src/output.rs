@@ -0,0 +1,63 @@
+use crate::cli::OutputFormat;
+use anyhow::Result;
+use serde::Serialize;
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+static DOCUMENT_EMITTED: AtomicBool = AtomicBool::new(false);
+
+/// Prints a line of human-readable output. With `--output json` stdout is reserved for
+/// the result document, so these lines go to stderr instead.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if $crate::output::is_json() {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
+pub fn init(format: OutputFormat) {
+    JSON_OUTPUT.store(format == OutputFormat::Json, Ordering::Relaxed);
+}
+
+pub fn is_json() -> bool {
+    JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Writes a command's result document to stdout. Does nothing for text output.
+pub fn emit<T: Serialize>(document: &T) -> Result<()> {
+    if !is_json() {
+        return Ok(());
+    }
+
+    println!("{}", serde_json::to_string_pretty(document)?);
+    DOCUMENT_EMITTED.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Reports a failed command as `{"error": ...}` on stdout, unless the command already
+/// emitted a document that carries the error itself.
+pub fn emit_error(error: &anyhow::Error) {
+    if is_json() && !DOCUMENT_EMITTED.load(Ordering::Relaxed) {
+        println!("{}", serde_json::json!({ "error": format!("{:#}", error) }));
+    }
+}
+
+/// Asks a yes/no question on stdin; anything but "y"/"yes" declines.
+pub fn confirm(prompt: &str) -> Result<bool> {
+    // Keep the prompt off stdout when it is reserved for JSON output
+    if is_json() {
+        eprint!("{} [y/N] ", prompt);
+        io::stderr().flush()?;
+    } else {
+        print!("{} [y/N] ", prompt);
+        io::stdout().flush()?;
+    }
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
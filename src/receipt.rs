@@ -0,0 +1,74 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::signature::Signature;
+use spl_token_client::client::RpcClientResponse;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Structured record of a token launch, rewritten after every completed step so a
+/// partial failure still leaves the mint address and landed signatures on disk.
+#[derive(Serialize, Deserialize)]
+pub struct LaunchReceipt {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub mint: String,
+    pub authority: String,
+    pub decimals: u8,
+    pub supply: u64,
+    pub auditor_elgamal_pubkey: Option<String>,
+    pub token_account: String,
+    pub mint_keypair_path: Option<String>,
+    pub signatures: Vec<StepSignature>,
+    pub complete: bool,
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StepSignature {
+    pub step: String,
+    pub signature: String,
+}
+
+impl LaunchReceipt {
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read launch receipt from {}", path.display()))?;
+        serde_json::from_str(&data).context("Invalid launch receipt")
+    }
+
+    pub fn record(&mut self, step: &str, signature: Signature) -> Result<()> {
+        self.signatures.push(StepSignature {
+            step: step.to_string(),
+            signature: signature.to_string(),
+        });
+        self.save()
+    }
+
+    pub fn record_response(&mut self, step: &str, response: RpcClientResponse) -> Result<()> {
+        match response_signature(response) {
+            Some(signature) => self.record(step, signature),
+            None => Ok(()),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let data = serde_json::to_string_pretty(self)?;
+        fs::write(path, data)
+            .with_context(|| format!("Failed to write launch receipt to {}", path.display()))
+    }
+}
+
+/// Extracts the signature of a transaction sent through the `Token` client.
+pub fn response_signature(response: RpcClientResponse) -> Option<Signature> {
+    match response {
+        RpcClientResponse::Signature(signature) => Some(signature),
+        RpcClientResponse::Transaction(tx) => tx.signatures.first().copied(),
+        RpcClientResponse::Simulation(_) => None,
+    }
+}